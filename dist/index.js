@@ -27559,8 +27559,28 @@ const core = __nccwpck_require__(7484);
 const exec = __nccwpck_require__(5236);
 const { spawn } = __nccwpck_require__(5317);
 const fs = __nccwpck_require__(9896);
+const os = __nccwpck_require__(857);
 const path = __nccwpck_require__(6928);
 
+// Same directory and prefix as the binary's other outputs (see OutputConfig in config.rs),
+// so jobs sharing a self-hosted runner don't overwrite each other's PID file
+function pidFilePath() {
+  const env = process.env;
+  const dir = env.TELEMETRY_OUTPUT_DIR ?? env.RUNNER_TEMP ?? os.tmpdir();
+  let prefix = env.TELEMETRY_OUTPUT_PREFIX;
+  if (prefix === undefined && env.GITHUB_RUN_ID !== undefined) {
+    prefix = `telemetry-${env.GITHUB_RUN_ID}`;
+    if (env.GITHUB_RUN_ATTEMPT !== undefined) {
+      prefix += `-${env.GITHUB_RUN_ATTEMPT}`;
+    }
+    if (env.GITHUB_JOB !== undefined) {
+      prefix += `-${env.GITHUB_JOB}`;
+    }
+    prefix = prefix.replace(/[^A-Za-z0-9_-]/gu, '_');
+  }
+  return path.join(dir, `${prefix ?? 'telemetry_data'}.pid`);
+}
+
 async function run() {
   try {
     const interval = core.getInput('interval') || '2';
@@ -27574,6 +27594,9 @@ async function run() {
     // Make binary executable
     await exec.exec('chmod', ['+x', telemetryBinary]);
 
+    // Let later steps run `telemetry mark <step>`
+    core.addPath(actionPath);
+
     // Start monitoring in background using spawn
     const child = spawn(telemetryBinary, [], {
       detached: true,
@@ -27590,7 +27613,7 @@ async function run() {
 
     // Get PID and save it
     const pid = child.pid;
-    const pidFile = '/tmp/telemetry.pid';
+    const pidFile = pidFilePath();
     fs.writeFileSync(pidFile, pid.toString());
 
     // Save PID to state for post action
@@ -0,0 +1,294 @@
+use crate::data::TelemetryData;
+use crate::reporters::Summary;
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// 予算で判定できる値
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// CPU使用率の平均 (%)
+    AvgCpu,
+    /// CPU使用率のピーク (%)
+    PeakCpu,
+    /// メモリ使用率の平均 (%)
+    AvgMemoryPercent,
+    /// メモリ使用率のピーク (%)
+    PeakMemoryPercent,
+    /// メモリ使用量の平均 (MB)
+    AvgMemoryMb,
+    /// メモリ使用量のピーク (MB)
+    PeakMemoryMb,
+    /// スワップ使用量のピーク (MB)
+    SwapUsed,
+    /// 記録時間 (秒)
+    JobDuration,
+}
+
+impl Metric {
+    const ALL: [Metric; 8] = [
+        Self::AvgCpu,
+        Self::PeakCpu,
+        Self::AvgMemoryPercent,
+        Self::PeakMemoryPercent,
+        Self::AvgMemoryMb,
+        Self::PeakMemoryMb,
+        Self::SwapUsed,
+        Self::JobDuration,
+    ];
+
+    /// 予算の式で使う名前
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::AvgCpu => "avg_cpu",
+            Self::PeakCpu => "peak_cpu",
+            Self::AvgMemoryPercent => "avg_memory_percent",
+            Self::PeakMemoryPercent => "peak_memory_percent",
+            Self::AvgMemoryMb => "avg_memory_mb",
+            Self::PeakMemoryMb => "peak_memory_mb",
+            Self::SwapUsed => "swap_used",
+            Self::JobDuration => "job_duration",
+        }
+    }
+
+    /// 記録データから値を求める (サンプルがなければNone)
+    pub fn value(self, data: &TelemetryData) -> Option<f64> {
+        let summary = Summary::from_data(data)?;
+        let value = match self {
+            Self::AvgCpu => summary.cpu_avg,
+            Self::PeakCpu => summary.cpu_peak,
+            Self::AvgMemoryPercent => summary.memory_avg_percent,
+            Self::PeakMemoryPercent => summary.memory_peak_percent,
+            Self::AvgMemoryMb => summary.memory_avg_mb,
+            Self::PeakMemoryMb => summary.memory_peak_mb as f64,
+            Self::SwapUsed => data.memory.iter().map(|s| s.swap_used_mb).max()? as f64,
+            Self::JobDuration => summary.duration_secs as f64,
+        };
+        // CPUとメモリの片方しか記録していなければ、もう片方の統計値は0になっている
+        let recorded = match self {
+            Self::AvgCpu | Self::PeakCpu => !data.cpu.is_empty(),
+            Self::JobDuration => true,
+            _ => !data.memory.is_empty(),
+        };
+        recorded.then_some(value)
+    }
+
+    /// 値を単位付きで表示
+    pub fn format(self, value: f64) -> String {
+        match self {
+            Self::AvgCpu | Self::PeakCpu | Self::AvgMemoryPercent | Self::PeakMemoryPercent => {
+                format!("{:.2}%", value)
+            }
+            Self::AvgMemoryMb | Self::PeakMemoryMb | Self::SwapUsed => format!("{:.0} MB", value),
+            Self::JobDuration => format_duration(value as u64),
+        }
+    }
+
+    /// 上限値に付けられる単位と倍率 (記録時間だけ `s` / `m` / `h` を付けられる)
+    fn unit_scale(self, unit: &str) -> Option<f64> {
+        match (self, unit) {
+            (_, "") => Some(1.0),
+            (Self::JobDuration, "s") => Some(1.0),
+            (Self::JobDuration, "m") => Some(60.0),
+            (Self::JobDuration, "h") => Some(3600.0),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Metric {
+    type Err = anyhow::Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match Self::ALL.into_iter().find(|metric| metric.name() == name) {
+            Some(metric) => Ok(metric),
+            None => {
+                let names: Vec<&str> = Self::ALL.iter().map(|metric| metric.name()).collect();
+                bail!("Unknown metric {:?} (expected one of {})", name, names.join(", "))
+            }
+        }
+    }
+}
+
+/// 比較演算子
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Le,
+    Lt,
+    Ge,
+    Gt,
+    Eq,
+}
+
+impl Op {
+    fn parse(op: &str) -> Self {
+        match op {
+            "<=" => Self::Le,
+            "<" => Self::Lt,
+            ">=" => Self::Ge,
+            ">" => Self::Gt,
+            _ => Self::Eq,
+        }
+    }
+
+    fn holds(self, value: f64, limit: f64) -> bool {
+        match self {
+            Self::Le => value <= limit,
+            Self::Lt => value < limit,
+            Self::Ge => value >= limit,
+            Self::Gt => value > limit,
+            Self::Eq => value == limit,
+        }
+    }
+}
+
+/// リソースの予算 (`peak_memory_mb <= 6000` のような式)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Budget {
+    pub metric: Metric,
+    pub op: Op,
+    /// 上限値 (記録時間は秒に換算済み)
+    pub limit: f64,
+    /// 設定に書かれた式 (表示用)
+    text: String,
+}
+
+impl FromStr for Budget {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let pattern = Regex::new(r"^\s*([a-z_]+)\s*(<=|>=|==|<|>)\s*(\d+(?:\.\d+)?)\s*([a-z]*)\s*$").unwrap();
+        let captures = pattern
+            .captures(text)
+            .with_context(|| format!("Invalid budget {:?} (expected e.g. \"peak_memory_mb <= 6000\")", text))?;
+
+        let metric: Metric = captures[1].parse().with_context(|| format!("Invalid budget {:?}", text))?;
+        let unit = &captures[4];
+        let scale = metric
+            .unit_scale(unit)
+            .with_context(|| format!("Invalid budget {:?}: unit {:?} is not allowed for {}", text, unit, metric.name()))?;
+
+        Ok(Self {
+            metric,
+            op: Op::parse(&captures[2]),
+            limit: captures[3].parse::<f64>()? * scale,
+            text: text.split_whitespace().collect::<Vec<_>>().join(" "),
+        })
+    }
+}
+
+impl TryFrom<String> for Budget {
+    type Error = anyhow::Error;
+
+    fn try_from(text: String) -> Result<Self> {
+        text.parse()
+    }
+}
+
+impl fmt::Display for Budget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// 予算の判定結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+    pub budget: Budget,
+    /// 記録データの値 (サンプルがなければNone)
+    pub actual: Option<f64>,
+}
+
+impl Outcome {
+    /// 予算を守れたか (値がなければ失敗とする)
+    pub fn passed(&self) -> bool {
+        self.actual.is_some_and(|actual| self.budget.op.holds(actual, self.budget.limit))
+    }
+
+    /// 値を単位付きで表示
+    pub fn actual_text(&self) -> String {
+        match self.actual {
+            Some(actual) => self.budget.metric.format(actual),
+            None => "no data".to_string(),
+        }
+    }
+}
+
+/// 記録データを予算と照らし合わせる
+pub fn evaluate(budgets: &[Budget], data: &TelemetryData) -> Vec<Outcome> {
+    budgets
+        .iter()
+        .map(|budget| Outcome { budget: budget.clone(), actual: budget.metric.value(data) })
+        .collect()
+}
+
+/// 秒を `20m 5s` の形式にする
+fn format_duration(secs: u64) -> String {
+    match (secs / 3600, secs / 60 % 60, secs % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m {}s", m, s),
+        (h, m, s) => format!("{}h {}m {}s", h, m, s),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::{CpuStats, MemoryStats};
+
+    #[test]
+    fn test_parse_budget() {
+        let budget: Budget = "job_duration<=20m".parse().unwrap();
+        assert_eq!(budget.metric, Metric::JobDuration);
+        assert_eq!(budget.op, Op::Le);
+        assert_eq!(budget.limit, 1200.0);
+
+        let budget: Budget = "  avg_cpu   >=  40.5 ".parse().unwrap();
+        assert_eq!((budget.metric, budget.op, budget.limit), (Metric::AvgCpu, Op::Ge, 40.5));
+        assert_eq!(budget.to_string(), "avg_cpu >= 40.5");
+
+        assert!("peak_memory <= 6000".parse::<Budget>().is_err());
+        assert!("peak_memory_mb <= 6g".parse::<Budget>().is_err());
+        assert!("swap_used = 0".parse::<Budget>().is_err());
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let cpu = |time: u64, total_load: f64| CpuStats {
+            time,
+            total_load,
+            user_load: total_load,
+            system_load: 0.0,
+            iowait_load: 0.0,
+            irq_load: 0.0,
+            softirq_load: 0.0,
+            steal_load: 0.0,
+            per_core: vec![],
+        };
+        let data = TelemetryData {
+            cpu: vec![cpu(0, 20.0), cpu(90_000, 40.0)],
+            memory: vec![
+                MemoryStats { time: 0, used_mb: 5000, swap_used_mb: 0, ..Default::default() },
+                MemoryStats { time: 90_000, used_mb: 7000, swap_used_mb: 12, ..Default::default() },
+            ],
+            ..Default::default()
+        };
+        let budgets: Vec<Budget> = ["peak_memory_mb <= 6000", "avg_cpu >= 30", "swap_used == 0", "job_duration <= 20m"]
+            .iter()
+            .map(|text| text.parse().unwrap())
+            .collect();
+
+        let outcomes = evaluate(&budgets, &data);
+        let passed: Vec<bool> = outcomes.iter().map(Outcome::passed).collect();
+        assert_eq!(passed, vec![false, true, false, true]);
+        assert_eq!(outcomes[0].actual_text(), "7000 MB");
+        assert_eq!(outcomes[3].actual_text(), "1m 30s");
+
+        let outcomes = evaluate(&budgets[..1], &TelemetryData::default());
+        assert!(!outcomes[0].passed());
+        assert_eq!(outcomes[0].actual_text(), "no data");
+    }
+}
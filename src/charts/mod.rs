@@ -1,6 +1,14 @@
-use crate::collectors::{CpuStats, MemoryStats};
+use crate::collectors::{CpuStats, DiskStats, MemoryStats, NetworkStats};
+use crate::data::{Step, TelemetryData};
 use anyhow::Result;
-use charts_rs::{LineChart, Series, Color, Box};
+use charts_rs::{LineChart, Series, Color, Box, NIL_VALUE};
+use regex::Regex;
+
+/// ステップの境界線の色
+const STEP_MARKER_COLOR: &str = "#9A60B4";
+
+/// 重ね描きするチャートのX軸の最大点数
+const MAX_OVERLAY_POINTS: u64 = 300;
 
 pub fn generate_cpu_chart(data: &[CpuStats]) -> Result<String> {
     if data.is_empty() {
@@ -14,23 +22,155 @@ pub fn generate_cpu_chart(data: &[CpuStats]) -> Result<String> {
         .map(|s| format!("{}s", (s.time - start_time) / 1000))
         .collect();
     
-    // CPU使用率データ
-    let cpu_values: Vec<f32> = data.iter()
-        .map(|s| s.total_load as f32)
+    // 内訳ごとのCPU使用率 (%)
+    // system_load は irq/softirq を含むため、積み上げ時は差し引いて重複を避ける
+    let breakdown = [
+        ("User", data.iter().map(|s| s.user_load).collect::<Vec<_>>()),
+        ("System", data.iter().map(|s| (s.system_load - s.irq_load - s.softirq_load).max(0.0)).collect()),
+        ("IRQ", data.iter().map(|s| s.irq_load).collect()),
+        ("SoftIRQ", data.iter().map(|s| s.softirq_load).collect()),
+        ("Steal", data.iter().map(|s| s.steal_load).collect()),
+        ("IOWait", data.iter().map(|s| s.iowait_load).collect()),
+    ];
+
+    // charts-rs には積み上げ機能がないため、累積値を塗りつぶして積み上げエリアを表現する
+    let mut stacked = vec![0.0f64; data.len()];
+    let series: Vec<Series> = breakdown.iter()
+        .map(|(name, values)| {
+            for (total, value) in stacked.iter_mut().zip(values) {
+                *total += value;
+            }
+            let mut series = Series::new(
+                name.to_string(),
+                stacked.iter().map(|v| v.min(100.0) as f32).collect(),
+            );
+            series.label_show = false;
+            series
+        })
         .collect();
-    
-    let mut chart = LineChart::new_with_theme(vec![
-        Series::new("CPU %".to_string(), cpu_values)
-    ], x_labels, "light");
+
+    let mut chart = LineChart::new_with_theme(series, x_labels, "light");
     
     chart.title_text = "CPU Usage".to_string();
     chart.width = 800.0;
     chart.height = 400.0;
+    chart.series_fill = true;
+
+    // 凡例をタイトルと被らない位置に配置
+    chart.legend_align = charts_rs::Align::Left;
+    chart.legend_margin = Some(Box {
+        top: 10.0,
+        left: 20.0,
+        right: 10.0,
+        bottom: 20.0,
+    });
+
+    // CPU軸: 0-100%固定
+    chart.y_axis_configs[0].axis_formatter = Some("{c}%".to_string());
+    chart.y_axis_configs[0].axis_min = Some(0.0);
+    chart.y_axis_configs[0].axis_max = Some(100.0);
     
     Ok(chart.svg()?)
 }
 
 
+/// コアごとのCPU使用率をヒートマップで描画
+///
+/// charts-rs の HeatmapChart は縦横のセル数が異なるとインデックスがずれるため、
+/// セルのグリッドは自前でSVGとして出力する。
+pub fn generate_cpu_heatmap_chart(data: &[CpuStats]) -> Result<String> {
+    const WIDTH: f64 = 800.0;
+    const MAX_COLUMNS: usize = 120;
+    const LEFT: f64 = 60.0;
+    const TOP: f64 = 50.0;
+    const BOTTOM: f64 = 40.0;
+    const ROW_HEIGHT: f64 = 20.0;
+
+    let core_count = data.iter().map(|s| s.per_core.len()).max().unwrap_or(0);
+    if data.is_empty() || core_count == 0 {
+        return Ok(String::new());
+    }
+
+    let start_time = data.first().unwrap().time;
+
+    // 列数が多すぎる場合は複数サンプルを平均して1列にまとめる
+    let bucket_size = data.len().div_ceil(MAX_COLUMNS);
+    let buckets: Vec<&[CpuStats]> = data.chunks(bucket_size).collect();
+
+    let plot_width = WIDTH - LEFT - 20.0;
+    let cell_width = plot_width / buckets.len() as f64;
+    let height = TOP + ROW_HEIGHT * core_count as f64 + BOTTOM;
+
+    let mut svg = format!(
+        "<svg width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" xmlns=\"http://www.w3.org/2000/svg\" font-family=\"Roboto, sans-serif\">\n",
+        w = WIDTH,
+        h = height
+    );
+    svg.push_str(&format!("<rect x=\"0\" y=\"0\" width=\"{}\" height=\"{}\" fill=\"#FFFFFF\"/>\n", WIDTH, height));
+    svg.push_str(&format!(
+        "<text x=\"{}\" y=\"28\" font-size=\"18\" font-weight=\"bold\" text-anchor=\"middle\" fill=\"#464646\">Per-core CPU Usage</text>\n",
+        WIDTH / 2.0
+    ));
+
+    for core in 0..core_count {
+        let y = TOP + ROW_HEIGHT * core as f64;
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" font-size=\"12\" text-anchor=\"end\" fill=\"#6E7079\">cpu{}</text>\n",
+            LEFT - 8.0,
+            y + ROW_HEIGHT * 0.7,
+            core
+        ));
+
+        for (column, bucket) in buckets.iter().enumerate() {
+            let values: Vec<f64> = bucket.iter()
+                .filter_map(|s| s.per_core.get(core).copied())
+                .collect();
+            if values.is_empty() {
+                continue;
+            }
+            let load = values.iter().sum::<f64>() / values.len() as f64;
+            svg.push_str(&format!(
+                "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" fill=\"{}\"><title>cpu{} {}s: {:.1}%</title></rect>\n",
+                LEFT + cell_width * column as f64,
+                y,
+                cell_width,
+                ROW_HEIGHT - 1.0,
+                heatmap_color(load),
+                core,
+                (bucket[0].time - start_time) / 1000,
+                load
+            ));
+        }
+    }
+
+    // X軸ラベル: 開始からの経過秒数 (最大10個程度に間引く)
+    let label_step = buckets.len().div_ceil(10).max(1);
+    for (column, bucket) in buckets.iter().enumerate().step_by(label_step) {
+        svg.push_str(&format!(
+            "<text x=\"{:.2}\" y=\"{:.2}\" font-size=\"12\" text-anchor=\"middle\" fill=\"#6E7079\">{}s</text>\n",
+            LEFT + cell_width * (column as f64 + 0.5),
+            height - BOTTOM + 18.0,
+            (bucket[0].time - start_time) / 1000
+        ));
+    }
+
+    svg.push_str("</svg>");
+    Ok(svg)
+}
+
+/// 使用率 (0-100%) をヒートマップの色に変換 (白 → 赤)
+fn heatmap_color(load: f64) -> String {
+    let ratio = (load / 100.0).clamp(0.0, 1.0);
+    let channel = |from: f64, to: f64| (from + (to - from) * ratio).round() as u8;
+    format!(
+        "#{:02X}{:02X}{:02X}",
+        channel(245.0, 255.0),
+        channel(245.0, 107.0),
+        channel(245.0, 107.0)
+    )
+}
+
+
 pub fn generate_memory_chart(data: &[MemoryStats]) -> Result<String> {
     if data.is_empty() {
         return Ok(String::new());
@@ -43,19 +183,131 @@ pub fn generate_memory_chart(data: &[MemoryStats]) -> Result<String> {
         .map(|s| format!("{}s", (s.time - start_time) / 1000))
         .collect();
     
-    // メモリ使用量（GB）
-    let memory_values: Vec<f32> = data.iter()
-        .map(|s| (s.used_mb as f64 / 1024.0) as f32)
+    // 内訳を持たない古いデータは使用量のみを描画
+    if data.iter().all(|s| s.free_mb == 0 && s.cached_mb == 0) {
+        let memory_values: Vec<f32> = data.iter()
+            .map(|s| (s.used_mb as f64 / 1024.0) as f32)
+            .collect();
+
+        let mut chart = LineChart::new_with_theme(vec![
+            Series::new("Memory GB".to_string(), memory_values)
+        ], x_labels, "light");
+
+        chart.title_text = "Memory Usage".to_string();
+        chart.width = 800.0;
+        chart.height = 400.0;
+
+        return Ok(chart.svg()?);
+    }
+
+    // 内訳ごとのメモリ使用量（MB）
+    let breakdown = [
+        ("Apps", data.iter().map(|s| s.app_used_mb()).collect::<Vec<_>>()),
+        ("Buffers", data.iter().map(|s| s.buffers_mb).collect()),
+        ("Cache", data.iter().map(|s| s.cached_mb + s.sreclaimable_mb).collect()),
+        ("Swap", data.iter().map(|s| s.swap_used_mb).collect()),
+    ];
+
+    // 累積値（GB）を塗りつぶして積み上げエリアを表現する
+    let mut stacked = vec![0u64; data.len()];
+    let series: Vec<Series> = breakdown.iter()
+        .map(|(name, values)| {
+            for (total, value) in stacked.iter_mut().zip(values) {
+                *total += value;
+            }
+            let mut series = Series::new(
+                format!("{} GB", name),
+                stacked.iter().map(|v| (*v as f64 / 1024.0) as f32).collect(),
+            );
+            series.label_show = false;
+            series
+        })
         .collect();
+
+    let mut chart = LineChart::new_with_theme(series, x_labels, "light");
+
+    chart.title_text = "Memory Usage".to_string();
+    chart.width = 800.0;
+    chart.height = 400.0;
+    chart.series_fill = true;
+
+    // 凡例をタイトルと被らない位置に配置
+    chart.legend_align = charts_rs::Align::Left;
+    chart.legend_margin = Some(Box {
+        top: 10.0,
+        left: 20.0,
+        right: 10.0,
+        bottom: 20.0,
+    });
+    chart.y_axis_configs[0].axis_formatter = Some("{c}GB".to_string());
+    chart.y_axis_configs[0].axis_min = Some(0.0);
     
+    Ok(chart.svg()?)
+}
+
+
+pub fn generate_network_chart(data: &[NetworkStats]) -> Result<String> {
+    if data.is_empty() {
+        return Ok(String::new());
+    }
+
+    let start_time = data.first().unwrap().time;
+
+    // X軸ラベル
+    let x_labels: Vec<String> = data.iter()
+        .map(|s| format!("{}s", (s.time - start_time) / 1000))
+        .collect();
+
+    // 受信・送信スループット（MB/s）
+    let rx_values: Vec<f32> = data.iter()
+        .map(|s| (s.rx_bytes_per_sec / 1024.0 / 1024.0) as f32)
+        .collect();
+    let tx_values: Vec<f32> = data.iter()
+        .map(|s| (s.tx_bytes_per_sec / 1024.0 / 1024.0) as f32)
+        .collect();
+
     let mut chart = LineChart::new_with_theme(vec![
-        Series::new("Memory GB".to_string(), memory_values)
+        Series::new("RX MB/s".to_string(), rx_values),
+        Series::new("TX MB/s".to_string(), tx_values),
     ], x_labels, "light");
-    
-    chart.title_text = "Memory Usage".to_string();
+
+    chart.title_text = "Network I/O".to_string();
     chart.width = 800.0;
     chart.height = 400.0;
-    
+
+    Ok(chart.svg()?)
+}
+
+
+pub fn generate_disk_chart(data: &[DiskStats]) -> Result<String> {
+    if data.is_empty() {
+        return Ok(String::new());
+    }
+
+    let start_time = data.first().unwrap().time;
+
+    // X軸ラベル
+    let x_labels: Vec<String> = data.iter()
+        .map(|s| format!("{}s", (s.time - start_time) / 1000))
+        .collect();
+
+    // 全デバイス合計の読み込み・書き込みスループット（MB/s）
+    let read_values: Vec<f32> = data.iter()
+        .map(|s| (s.total_read_bytes_per_sec() / 1024.0 / 1024.0) as f32)
+        .collect();
+    let write_values: Vec<f32> = data.iter()
+        .map(|s| (s.total_write_bytes_per_sec() / 1024.0 / 1024.0) as f32)
+        .collect();
+
+    let mut chart = LineChart::new_with_theme(vec![
+        Series::new("Read MB/s".to_string(), read_values),
+        Series::new("Write MB/s".to_string(), write_values),
+    ], x_labels, "light");
+
+    chart.title_text = "Disk I/O".to_string();
+    chart.width = 800.0;
+    chart.height = 400.0;
+
     Ok(chart.svg()?)
 }
 
@@ -82,9 +334,9 @@ pub fn generate_combined_chart(cpu_data: &[CpuStats], memory_data: &[MemoryStats
         .map(|s| (s.used_mb as f64 / 1024.0) as f32)
         .collect();
 
-    // メモリの最大容量（GB）
+    // メモリの最大容量（GB）: cgroupの上限があればそちらを使う
     let max_memory_gb = memory_data.first()
-        .map(|s| (s.total_mb as f64 / 1024.0) as f32)
+        .map(|s| (s.limit_mb.unwrap_or(s.total_mb) as f64 / 1024.0) as f32)
         .unwrap_or(16.0);
 
     // CPU用のSeries
@@ -143,3 +395,321 @@ pub fn generate_combined_chart(cpu_data: &[CpuStats], memory_data: &[MemoryStats
     // SVG生成
     Ok(chart.svg()?)
 }
+
+
+/// 2つの記録のCPU使用率とメモリ使用量を、それぞれの最初のサンプルからの経過時間で重ねて描画
+///
+/// サンプルの時刻は記録ごとに異なるため、共通の経過時間の目盛りごとに直前のサンプルの値を取る。
+/// 短い方の記録は終了後の点を描かない。
+pub fn generate_overlay_chart(baseline: &TelemetryData, current: &TelemetryData) -> Result<String> {
+    let (Some(baseline_start), Some(current_start)) = (start_time(baseline), start_time(current)) else {
+        return Ok(String::new());
+    };
+    let duration = |data: &TelemetryData, start: u64| {
+        let cpu_end = data.cpu.last().map_or(start, |s| s.time);
+        let memory_end = data.memory.last().map_or(start, |s| s.time);
+        cpu_end.max(memory_end) - start
+    };
+    let duration = duration(baseline, baseline_start).max(duration(current, current_start));
+
+    // 目盛りの間隔: 基準の記録のサンプル間隔 (点が多すぎる場合は広げる)
+    let interval = match (baseline.cpu.first(), baseline.cpu.last()) {
+        (Some(first), Some(last)) if baseline.cpu.len() > 1 => (last.time - first.time) / (baseline.cpu.len() as u64 - 1),
+        _ => 1000,
+    };
+    let interval = interval.max(1000).max(duration / MAX_OVERLAY_POINTS + 1);
+    let grid: Vec<u64> = (0..=duration / interval).map(|i| i * interval).collect();
+
+    let x_labels: Vec<String> = grid.iter().map(|elapsed| format!("{}s", elapsed / 1000)).collect();
+
+    let cpu = |data: &TelemetryData, start: u64| {
+        let samples: Vec<(u64, f32)> = data.cpu.iter().map(|s| (s.time - start, s.total_load as f32)).collect();
+        resample(&samples, &grid)
+    };
+    let memory = |data: &TelemetryData, start: u64| {
+        let samples: Vec<(u64, f32)> =
+            data.memory.iter().map(|s| (s.time - start, (s.used_mb as f64 / 1024.0) as f32)).collect();
+        resample(&samples, &grid)
+    };
+
+    let series_on = |name: &str, values: Vec<f32>, y_axis_index: usize| {
+        let mut series = Series::new(name.to_string(), values);
+        series.y_axis_index = y_axis_index;
+        series.label_show = false;
+        series
+    };
+    let mut chart = LineChart::new_with_theme(
+        vec![
+            series_on("Baseline CPU %", cpu(baseline, baseline_start), 0),
+            series_on("Current CPU %", cpu(current, current_start), 0),
+            series_on("Baseline Memory GB", memory(baseline, baseline_start), 1),
+            series_on("Current Memory GB", memory(current, current_start), 1),
+        ],
+        x_labels,
+        "light",
+    );
+
+    chart.title_text = "Baseline vs Current".to_string();
+    chart.width = 1000.0;
+    chart.height = 500.0;
+
+    // 凡例を左寄せにして、タイトルと被らない位置に配置
+    chart.legend_align = charts_rs::Align::Left;
+    chart.legend_margin = Some(Box {
+        top: 10.0,
+        left: 20.0,
+        right: 10.0,
+        bottom: 20.0,
+    });
+
+    // 基準の記録は薄い色、比較する記録は統合グラフと同じ色 (CPU: 赤、メモリ: 緑)
+    chart.series_colors = vec![
+        Color::from("#FFC9C9"),
+        Color::from("#FF6B6B"),
+        Color::from("#B2F2BB"),
+        Color::from("#51CF66"),
+    ];
+
+    // 2つ目のY軸を追加
+    chart.y_axis_configs.push(chart.y_axis_configs[0].clone());
+
+    // CPU軸（左）の設定：0-100%固定
+    chart.y_axis_configs[0].axis_formatter = Some("{c}%".to_string());
+    chart.y_axis_configs[0].axis_min = Some(0.0);
+    chart.y_axis_configs[0].axis_max = Some(100.0);
+
+    // メモリ軸（右）の設定
+    chart.y_axis_configs[1].axis_formatter = Some("{c}GB".to_string());
+    chart.y_axis_configs[1].axis_min = Some(0.0);
+
+    Ok(chart.svg()?)
+}
+
+/// 記録の最初のサンプルの時刻 (CPU、なければメモリ)
+fn start_time(data: &TelemetryData) -> Option<u64> {
+    data.cpu.first().map(|s| s.time).or_else(|| data.memory.first().map(|s| s.time))
+}
+
+/// (経過時間, 値) のサンプル列から、目盛りごとに直前のサンプルの値を取る
+///
+/// 最初のサンプルより前と最後のサンプルより後は `NIL_VALUE` (点を描かない) にする。
+fn resample(samples: &[(u64, f32)], grid: &[u64]) -> Vec<f32> {
+    let end = samples.last().map_or(0, |(elapsed, _)| *elapsed);
+    grid.iter()
+        .map(|&elapsed| match samples.iter().rev().find(|(t, _)| *t <= elapsed) {
+            Some((_, value)) if elapsed <= end => *value,
+            _ => NIL_VALUE,
+        })
+        .collect()
+}
+
+/// LineChart のSVGにステップの境界線と名前を重ねる
+///
+/// charts-rs には任意のX座標に縦線を引く機能がないため、出力されたSVGのグリッドとX軸の線から
+/// プロット領域を求め、点と同じ計算 (各カテゴリの中央) で時刻をX座標に変換する。
+/// `times` はチャートのX軸に並べたサンプルの時刻。プロット領域が見つからなければ警告してそのまま返す
+/// (charts-rs の出力が変わった場合に気付けるよう、マーカーを描く全チャートをテストで確かめている)。
+pub fn draw_step_markers(svg: String, times: &[u64], steps: &[Step]) -> String {
+    if steps.is_empty() || times.is_empty() {
+        return svg;
+    }
+    let (Some((left, top, right, bottom)), Some(end)) = (plot_area(&svg), svg.rfind("</svg>")) else {
+        eprintln!("Step markers are not drawn: the chart's plot area was not found");
+        return svg;
+    };
+
+    let unit_width = (right - left) / times.len() as f64;
+    let x_of = |time: u64| left + unit_width * (fractional_index(times, time) + 0.5);
+    let line = |x: f64| {
+        format!(
+            "<line x1=\"{x:.1}\" y1=\"{top:.1}\" x2=\"{x:.1}\" y2=\"{bottom:.1}\" stroke=\"{STEP_MARKER_COLOR}\" stroke-width=\"1\" stroke-dasharray=\"4,3\"/>\n"
+        )
+    };
+
+    let mut markers = String::from("<g font-family=\"Roboto\" font-size=\"11\">\n");
+    for (i, step) in steps.iter().enumerate() {
+        let x = x_of(step.start_time);
+        markers.push_str(&line(x));
+        // 近いステップの名前が重ならないよう3段にずらす
+        markers.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\" fill=\"{}\">{}<title>{}</title></text>\n",
+            x + 3.0,
+            top + 12.0 + 13.0 * (i % 3) as f64,
+            STEP_MARKER_COLOR,
+            escape_xml(&step.name),
+            escape_xml(&step.name)
+        ));
+
+        // 次のステップがすぐに始まらない場合は終了位置にも線を引く
+        if let Some(end_time) = step.end_time {
+            if steps.get(i + 1).is_none_or(|next| next.start_time != end_time) {
+                markers.push_str(&line(x_of(end_time)));
+            }
+        }
+    }
+    markers.push_str("</g>\n");
+
+    let mut svg = svg;
+    svg.insert_str(end, &markers);
+    svg
+}
+
+/// charts-rs が出力したSVGからプロット領域 (left, top, right, bottom) を求める
+///
+/// 上端は最初のグリッド線、左右と下端はX軸の線から取る。チャートの大きさに収まらなければ
+/// 別の要素を拾ったとみなして None を返す
+fn plot_area(svg: &str) -> Option<(f64, f64, f64, f64)> {
+    let size = Regex::new(r#"^<svg width="([\d.]+)" height="([\d.]+)""#).unwrap();
+    let grid = Regex::new(r##"<g stroke="#E0E6F2">\s*<line stroke-width="1" x1="[\d.]+" y1="([\d.]+)""##).unwrap();
+    let axis = Regex::new(
+        r##"<g stroke="#6E7079">\s*<line stroke-width="1" x1="([\d.]+)" y1="([\d.]+)" x2="([\d.]+)""##,
+    )
+    .unwrap();
+
+    let size = size.captures(svg)?;
+    let (width, height): (f64, f64) = (size[1].parse().ok()?, size[2].parse().ok()?);
+    let top = grid.captures(svg)?[1].parse().ok()?;
+    let axis = axis.captures(svg)?;
+    let (left, right, bottom) = (axis[1].parse().ok()?, axis[3].parse().ok()?, axis[2].parse().ok()?);
+    (0.0 <= left && left < right && right <= width && 0.0 <= top && top < bottom && bottom <= height)
+        .then_some((left, top, right, bottom))
+}
+
+/// 時刻がサンプル列の何番目にあたるかを前後のサンプルから線形補間で求める (範囲外は端に寄せる)
+fn fractional_index(times: &[u64], time: u64) -> f64 {
+    match times.iter().position(|&t| t >= time) {
+        Some(0) => 0.0,
+        Some(i) => {
+            let (prev, next) = (times[i - 1], times[i]);
+            (i - 1) as f64 + (time - prev) as f64 / (next - prev) as f64
+        }
+        None => (times.len() - 1) as f64,
+    }
+}
+
+/// SVGのテキストとして安全な形にエスケープ
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw_step_markers() {
+        let memory: Vec<MemoryStats> = (0..5)
+            .map(|i| MemoryStats { time: 1000 * (i + 1), used_mb: 1024, ..Default::default() })
+            .collect();
+        let times: Vec<u64> = memory.iter().map(|s| s.time).collect();
+        let steps = vec![
+            Step { name: "build".to_string(), start_time: 1000, end_time: Some(3500) },
+            Step { name: "test <unit>".to_string(), start_time: 4000, end_time: None },
+        ];
+
+        let svg = generate_memory_chart(&memory).unwrap();
+        let (left, _, right, _) = plot_area(&svg).expect("Plot area not found");
+        let marked = draw_step_markers(svg, &times, &steps);
+
+        // 5サンプルなら1つ目は1/10、3500ms は 2.5番目なので 6/10 の位置
+        let unit = (right - left) / 5.0;
+        assert!(marked.contains(&format!("x1=\"{:.1}\"", left + unit * 0.5)));
+        assert!(marked.contains(&format!("x1=\"{:.1}\"", left + unit * 3.0)));
+        assert!(marked.contains("test &lt;unit&gt;"));
+        assert!(marked.ends_with("</g>\n</svg>"));
+
+        assert_eq!(fractional_index(&times, 0), 0.0);
+        assert_eq!(fractional_index(&times, 9000), 4.0);
+    }
+
+    #[test]
+    fn test_plot_area_of_marked_charts() {
+        use crate::collectors::disk::DiskDeviceStats;
+        use crate::collectors::NetworkStats;
+
+        let times: Vec<u64> = (1..=5).map(|i| i * 1000).collect();
+        let cpu: Vec<CpuStats> = times
+            .iter()
+            .map(|&time| CpuStats {
+                time,
+                total_load: 50.0,
+                user_load: 40.0,
+                system_load: 10.0,
+                iowait_load: 5.0,
+                irq_load: 0.0,
+                softirq_load: 0.0,
+                steal_load: 0.0,
+                per_core: vec![],
+            })
+            .collect();
+        let memory: Vec<MemoryStats> =
+            times.iter().map(|&time| MemoryStats { time, used_mb: 1024, ..Default::default() }).collect();
+        let network: Vec<NetworkStats> = times
+            .iter()
+            .map(|&time| NetworkStats {
+                time,
+                rx_bytes_per_sec: 1e6,
+                tx_bytes_per_sec: 2e5,
+                rx_packets_per_sec: 0.0,
+                tx_packets_per_sec: 0.0,
+                rx_errors: 0,
+                tx_errors: 0,
+                rx_drops: 0,
+                tx_drops: 0,
+            })
+            .collect();
+        let disk: Vec<DiskStats> = times
+            .iter()
+            .map(|&time| DiskStats {
+                time,
+                devices: vec![DiskDeviceStats {
+                    name: "sda".to_string(),
+                    read_bytes_per_sec: 1e6,
+                    write_bytes_per_sec: 3e6,
+                    read_iops: 0.0,
+                    write_iops: 0.0,
+                    busy_percent: 0.0,
+                }],
+            })
+            .collect();
+        let steps = vec![Step { name: "build".to_string(), start_time: 2000, end_time: None }];
+
+        // render でステップの境界を描くチャートはすべてプロット領域が見つかる
+        let charts = [
+            ("combined", generate_combined_chart(&cpu, &memory)),
+            ("cpu", generate_cpu_chart(&cpu)),
+            ("memory", generate_memory_chart(&memory)),
+            ("network", generate_network_chart(&network)),
+            ("disk", generate_disk_chart(&disk)),
+        ];
+        for (name, svg) in charts {
+            let svg = svg.unwrap();
+            assert!(plot_area(&svg).is_some(), "Plot area of the {} chart was not found", name);
+            assert!(draw_step_markers(svg, &times, &steps).contains(">build<title>"), "No markers on the {} chart", name);
+        }
+        assert_eq!(plot_area("<svg width=\"800\" height=\"400\"></svg>"), None);
+    }
+
+    #[test]
+    fn test_generate_overlay_chart() {
+        let samples = [(0, 10.0), (2000, 20.0), (4000, 30.0)];
+        assert_eq!(
+            resample(&samples, &[0, 1000, 2000, 3000, 4000, 5000]),
+            vec![10.0, 10.0, 20.0, 20.0, 30.0, NIL_VALUE]
+        );
+        assert_eq!(resample(&samples[1..], &[0, 2000]), vec![NIL_VALUE, 20.0]);
+
+        let memory = |count: u64| TelemetryData {
+            memory: (0..count)
+                .map(|i| MemoryStats { time: 50_000 + 2000 * i, used_mb: 1024 * (i + 1), ..Default::default() })
+                .collect(),
+            ..Default::default()
+        };
+        let svg = generate_overlay_chart(&memory(5), &memory(8)).unwrap();
+        assert!(svg.contains("Baseline Memory GB"));
+        assert!(svg.contains("Current Memory GB"));
+        // 長い方 (14秒) に合わせて目盛りを並べる
+        assert!(svg.contains("\n14s\n"));
+        assert!(generate_overlay_chart(&memory(5), &TelemetryData::default()).unwrap().is_empty());
+    }
+}
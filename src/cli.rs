@@ -0,0 +1,298 @@
+// doc コメントはそのまま --help に表示されるため英語で書く
+
+use crate::budget::Budget;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+/// Record and visualize system telemetry during CI jobs
+#[derive(Debug, Parser)]
+#[command(name = "telemetry", version)]
+pub struct Cli {
+    /// Path to a TOML config file [env: TELEMETRY_CONFIG]
+    #[arg(long, global = true, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Directory for recorded data and charts [env: TELEMETRY_OUTPUT_DIR]
+    #[arg(long, global = true, value_name = "DIR")]
+    pub output_dir: Option<PathBuf>,
+
+    /// File name prefix for recorded data and charts [env: TELEMETRY_OUTPUT_PREFIX]
+    #[arg(long, global = true, value_name = "PREFIX")]
+    pub prefix: Option<String>,
+
+    /// Deprecated alias for `render <FILE>`
+    #[arg(long = "generate-svg", value_name = "FILE", hide = true)]
+    pub generate_svg: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Record telemetry until stopped (default when no subcommand is given)
+    Record(RecordArgs),
+    /// Run a command while recording, and exit with its exit status
+    Run(RunArgs),
+    /// Mark the start of a workflow step in the running recording
+    Mark(MarkArgs),
+    /// Render SVG charts from recorded data into the output directory
+    Render {
+        /// Recorded data (JSON or sample log) [default: this run's recording]
+        input: Option<PathBuf>,
+    },
+    /// Write a Markdown report of recorded data with the charts embedded
+    Report(ReportArgs),
+    /// Write a self-contained interactive HTML report
+    Html {
+        /// Recorded data (JSON or sample log) [default: this run's recording]
+        input: Option<PathBuf>,
+        /// Write the report to FILE [default: <dir>/<prefix>-report.html]
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Write the report to the job summary, usage to step outputs, and
+    /// threshold violations as annotations (for GitHub Actions)
+    Github {
+        /// Recorded data (JSON or sample log) [default: this run's recording]
+        input: Option<PathBuf>,
+        /// Link charts at URL/<prefix>-<chart>.svg instead of inlining them as data URIs
+        #[arg(long, value_name = "URL")]
+        chart_url: Option<String>,
+    },
+    /// Write a pull request comment body to a file (post it with e.g. `gh`)
+    Comment(CommentArgs),
+    /// Print average and peak usage of recorded data
+    Summary {
+        /// Recorded data (JSON or sample log) [default: this run's recording]
+        input: Option<PathBuf>,
+    },
+    /// Check recorded data against resource budgets, failing if any is exceeded
+    Check(CheckArgs),
+    /// Compare two recordings aligned by elapsed time, and chart them together
+    Compare(CompareArgs),
+    /// Convert recorded data to another format
+    Export {
+        /// Recorded data (JSON or sample log) [default: this run's recording]
+        input: Option<PathBuf>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+        /// Write to FILE instead of stdout
+        #[arg(long, short, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Default, Args)]
+pub struct RecordArgs {
+    /// Seconds between samples [env: TELEMETRY_INTERVAL]
+    #[arg(long, value_name = "SECS")]
+    pub interval: Option<u64>,
+    /// Stop after this many samples [env: TELEMETRY_ITERATIONS]
+    #[arg(long, value_name = "N")]
+    pub iterations: Option<u64>,
+    /// Serve the latest values at http://ADDR/metrics for Prometheus, e.g. 127.0.0.1:9464
+    /// [env: TELEMETRY_LISTEN]
+    #[arg(long, value_name = "ADDR")]
+    pub listen: Option<SocketAddr>,
+}
+
+#[derive(Debug, Args)]
+pub struct RunArgs {
+    /// Seconds between samples [env: TELEMETRY_INTERVAL]
+    #[arg(long, value_name = "SECS")]
+    pub interval: Option<u64>,
+    /// Serve the latest values at http://ADDR/metrics for Prometheus, e.g. 127.0.0.1:9464
+    /// [env: TELEMETRY_LISTEN]
+    #[arg(long, value_name = "ADDR")]
+    pub listen: Option<SocketAddr>,
+    /// Command to run, e.g. `telemetry run -- cargo build --release`
+    #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true, value_name = "COMMAND")]
+    pub command: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct ReportArgs {
+    /// Recorded data (JSON or sample log) [default: this run's recording]
+    pub input: Option<PathBuf>,
+    /// Write the report to FILE instead of stdout
+    #[arg(long, short, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+    /// Append the report to $GITHUB_STEP_SUMMARY instead of stdout
+    #[arg(long)]
+    pub step_summary: bool,
+    /// Link charts at URL/<prefix>-<chart>.svg (e.g. where `render` output is published)
+    /// instead of inlining them as data URIs
+    #[arg(long, value_name = "URL")]
+    pub chart_url: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct CommentArgs {
+    /// Recorded data (JSON or sample log) [default: this run's recording]
+    pub input: Option<PathBuf>,
+    /// Recorded data to compare against (e.g. from the base branch)
+    #[arg(long, value_name = "FILE")]
+    pub baseline: Option<PathBuf>,
+    /// Link charts at URL/<prefix>-<chart>.svg (e.g. where `render` output is published)
+    #[arg(long, value_name = "URL")]
+    pub chart_url: Option<String>,
+    /// Distinguish this comment from others on the same pull request (e.g. the job name)
+    #[arg(long, value_name = "ID")]
+    pub id: Option<String>,
+    /// Write the comment to FILE [default: <dir>/<prefix>-comment.md]
+    #[arg(long, short, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct CheckArgs {
+    /// Recorded data (JSON or sample log) [default: this run's recording]
+    pub input: Option<PathBuf>,
+    /// Budget to check in addition to the config, e.g. `peak_memory_mb <= 6000` or
+    /// `job_duration <= 20m` (repeatable) [env: TELEMETRY_BUDGETS]
+    #[arg(long = "budget", value_name = "EXPR")]
+    pub budgets: Vec<Budget>,
+    /// Also write the results as JUnit XML to FILE
+    #[arg(long, value_name = "FILE")]
+    pub junit: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct CompareArgs {
+    /// Recorded data to compare against
+    pub baseline: PathBuf,
+    /// Recorded data to compare
+    pub current: PathBuf,
+    /// Skip the first SECS seconds of the current run (of the baseline if negative)
+    #[arg(long, value_name = "SECS", default_value_t = 0, allow_negative_numbers = true)]
+    pub offset: i64,
+    /// Start each run at its first sample with CPU usage of 5% or more
+    #[arg(long)]
+    pub trim_idle: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct MarkArgs {
+    /// Name of the step that starts now (ends the current step, if any)
+    #[arg(required_unless_present = "end")]
+    pub name: Option<String>,
+    /// End the current step without starting a new one
+    #[arg(long, conflicts_with = "name")]
+    pub end: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// Pretty-printed JSON (the format written at the end of `record`)
+    Json,
+    /// One row per sample and one column per metric, for spreadsheets and notebooks
+    Csv,
+    /// OpenMetrics text with a timestamp per sample, for
+    /// `promtool tsdb create-blocks-from openmetrics`
+    Openmetrics,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_subcommands() {
+        let cli = Cli::try_parse_from(["telemetry"]).unwrap();
+        assert!(cli.command.is_none());
+
+        let cli = Cli::try_parse_from(["telemetry", "record", "--interval", "2", "--config", "t.toml"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Record(RecordArgs { interval: Some(2), .. }))));
+        assert_eq!(cli.config, Some(PathBuf::from("t.toml")));
+
+        let cli = Cli::try_parse_from([
+            "telemetry", "run", "--interval", "1", "--listen", "127.0.0.1:9464", "--", "cargo", "build", "--release",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Command::Run(args)) => {
+                assert_eq!(args.interval, Some(1));
+                assert_eq!(args.listen, Some(SocketAddr::from(([127, 0, 0, 1], 9464))));
+                assert_eq!(args.command, vec!["cargo", "build", "--release"]);
+            }
+            command => panic!("Unexpected command: {:?}", command),
+        }
+        assert!(Cli::try_parse_from(["telemetry", "run"]).is_err());
+        assert!(Cli::try_parse_from(["telemetry", "record", "--listen", "9464"]).is_err());
+
+        let cli = Cli::try_parse_from(["telemetry", "mark", "cargo test"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Mark(MarkArgs { name: Some(name), end: false })) if name == "cargo test"));
+        let cli = Cli::try_parse_from(["telemetry", "mark", "--end"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Mark(MarkArgs { name: None, end: true }))));
+        assert!(Cli::try_parse_from(["telemetry", "mark"]).is_err());
+        assert!(Cli::try_parse_from(["telemetry", "mark", "build", "--end"]).is_err());
+
+        let cli = Cli::try_parse_from(["telemetry", "report", "--step-summary", "--chart-url", "https://example.com"]).unwrap();
+        match cli.command {
+            Some(Command::Report(args)) => {
+                assert!(args.step_summary);
+                assert_eq!(args.chart_url.as_deref(), Some("https://example.com"));
+                assert_eq!(args.input, None);
+            }
+            command => panic!("Unexpected command: {:?}", command),
+        }
+
+        let cli = Cli::try_parse_from(["telemetry", "github", "data.ndjson", "--chart-url", "https://example.com"]).unwrap();
+        match cli.command {
+            Some(Command::Github { input, chart_url }) => {
+                assert_eq!(input.as_deref(), Some(std::path::Path::new("data.ndjson")));
+                assert_eq!(chart_url.as_deref(), Some("https://example.com"));
+            }
+            command => panic!("Unexpected command: {:?}", command),
+        }
+
+        let cli = Cli::try_parse_from(["telemetry", "html", "-o", "report.html"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Html { input: None, output: Some(_) })));
+
+        let cli = Cli::try_parse_from(["telemetry", "comment", "--baseline", "base.json", "--id", "build"]).unwrap();
+        match cli.command {
+            Some(Command::Comment(args)) => {
+                assert_eq!(args.baseline, Some(PathBuf::from("base.json")));
+                assert_eq!(args.id.as_deref(), Some("build"));
+                assert_eq!(args.output, None);
+            }
+            command => panic!("Unexpected command: {:?}", command),
+        }
+
+        let cli = Cli::try_parse_from([
+            "telemetry", "check", "--budget", "avg_cpu >= 40", "--budget", "swap_used == 0", "--junit", "budgets.xml",
+        ])
+        .unwrap();
+        match cli.command {
+            Some(Command::Check(args)) => {
+                assert_eq!(args.budgets.len(), 2);
+                assert_eq!(args.junit, Some(PathBuf::from("budgets.xml")));
+            }
+            command => panic!("Unexpected command: {:?}", command),
+        }
+        assert!(Cli::try_parse_from(["telemetry", "check", "--budget", "avg_cpu"]).is_err());
+
+        let cli = Cli::try_parse_from(["telemetry", "compare", "a.json", "b.json"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Compare(CompareArgs { offset: 0, trim_idle: false, .. }))));
+        let cli = Cli::try_parse_from(["telemetry", "compare", "a.json", "b.json", "--offset", "-30", "--trim-idle"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Compare(CompareArgs { offset: -30, trim_idle: true, .. }))));
+
+        let cli = Cli::try_parse_from(["telemetry", "render", "--prefix", "nightly"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Render { input: None })));
+        assert_eq!(cli.prefix.as_deref(), Some("nightly"));
+
+        assert!(Cli::try_parse_from(["telemetry", "compare", "a.json"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_legacy_generate_svg() {
+        let cli = Cli::try_parse_from(["telemetry", "--generate-svg", "/tmp/telemetry_data.json"]).unwrap();
+        assert_eq!(cli.generate_svg, Some(PathBuf::from("/tmp/telemetry_data.json")));
+        assert!(cli.command.is_none());
+
+        assert!(Cli::try_parse_from(["telemetry", "--generate-svg"]).is_err());
+    }
+}
@@ -0,0 +1,232 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// cgroup v2 のマウントポイント候補 (hybrid 構成では unified 配下)
+const CGROUP2_MOUNTS: [&str; 2] = ["/sys/fs/cgroup", "/sys/fs/cgroup/unified"];
+
+/// cgroup統計データ
+#[derive(Debug, Clone, Serialize, serde::Deserialize)]
+pub struct CgroupStats {
+    /// タイムスタンプ (ミリ秒)
+    pub time: u64,
+    /// cgroup内の使用中メモリ (MB) (memory.current)
+    pub memory_current_mb: u64,
+    /// cgroupのメモリ上限 (MB) (memory.max、無制限ならNone)
+    pub memory_max_mb: Option<u64>,
+    /// OOM発生回数の累計 (memory.events の oom)
+    pub oom: u64,
+    /// OOM Killer によるkill回数の累計 (memory.events の oom_kill)
+    pub oom_kill: u64,
+    /// CPUクォータ (コア数換算、cpu.max、無制限ならNone)
+    pub cpu_quota_cores: Option<f64>,
+    /// 前回からのスケジューリング期間数 (cpu.stat の nr_periods)
+    pub nr_periods: u64,
+    /// 前回からのスロットリングされた期間数 (cpu.stat の nr_throttled)
+    pub nr_throttled: u64,
+    /// 前回からのスロットリング時間 (マイクロ秒) (cpu.stat の throttled_usec)
+    pub throttled_usec: u64,
+}
+
+/// 自プロセスが所属する cgroup v2 ディレクトリ
+#[derive(Debug, Clone)]
+pub struct Cgroup {
+    path: PathBuf,
+}
+
+impl Cgroup {
+    /// /proc/self/cgroup から cgroup v2 のパスを検出 (v2 が使えない場合はNone)
+    pub fn detect() -> Option<Self> {
+        let content = fs::read_to_string("/proc/self/cgroup").ok()?;
+        let relative = Self::parse_proc_self_cgroup(&content)?;
+
+        CGROUP2_MOUNTS
+            .iter()
+            .map(|mount| Path::new(mount).join(relative.trim_start_matches('/')))
+            .find(|path| path.join("cgroup.controllers").exists())
+            .map(|path| Self { path })
+    }
+
+    /// 使用中メモリ (bytes)
+    pub fn memory_current(&self) -> Option<u64> {
+        self.read("memory.current")?.trim().parse().ok()
+    }
+
+    /// メモリ上限 (bytes、無制限ならNone)
+    pub fn memory_max(&self) -> Option<u64> {
+        Self::parse_max_value(&self.read("memory.max")?)
+    }
+
+    /// memory.stat の内容 ("key value" 形式、値は bytes)
+    pub fn memory_stat(&self) -> Option<String> {
+        self.read("memory.stat")
+    }
+
+    /// 使用中スワップ (bytes、スワップを計上していなければNone)
+    pub fn swap_current(&self) -> Option<u64> {
+        self.read("memory.swap.current")?.trim().parse().ok()
+    }
+
+    /// スワップ上限 (bytes、無制限ならNone)
+    pub fn swap_max(&self) -> Option<u64> {
+        Self::parse_max_value(&self.read("memory.swap.max")?)
+    }
+
+    /// CPUクォータ (コア数換算、無制限ならNone)
+    pub fn cpu_quota_cores(&self) -> Option<f64> {
+        Self::parse_cpu_max(&self.read("cpu.max")?)
+    }
+
+    /// "key value" 形式のファイルから値を取得 (存在しなければ0)
+    fn keyed_value(&self, file: &str, key: &str) -> u64 {
+        self.read(file)
+            .map(|content| Self::parse_keyed_value(&content, key))
+            .unwrap_or(0)
+    }
+
+    fn read(&self, file: &str) -> Option<String> {
+        fs::read_to_string(self.path.join(file)).ok()
+    }
+
+    /// /proc/self/cgroup から cgroup v2 (階層ID 0) のパスを取り出す
+    fn parse_proc_self_cgroup(content: &str) -> Option<String> {
+        content
+            .lines()
+            .find_map(|line| line.strip_prefix("0::"))
+            .map(|path| path.trim().to_string())
+    }
+
+    /// memory.max のような "max" または数値のファイルをパース
+    fn parse_max_value(content: &str) -> Option<u64> {
+        match content.trim() {
+            "max" => None,
+            value => value.parse().ok(),
+        }
+    }
+
+    /// cpu.max ("$MAX $PERIOD") をコア数に換算
+    fn parse_cpu_max(content: &str) -> Option<f64> {
+        let mut parts = content.split_whitespace();
+        let quota: f64 = parts.next()?.parse().ok()?;
+        let period: f64 = parts.next()?.parse().ok()?;
+        if period > 0.0 {
+            Some(quota / period)
+        } else {
+            None
+        }
+    }
+
+    /// memory.events / cpu.stat のような "key value" 形式から値を取り出す
+    pub(super) fn parse_keyed_value(content: &str, key: &str) -> u64 {
+        content
+            .lines()
+            .find_map(|line| {
+                let (k, v) = line.split_once(' ')?;
+                if k == key {
+                    v.trim().parse().ok()
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(0)
+    }
+}
+
+/// cpu.stat から読み取った生のスロットリングカウンタ
+#[derive(Debug, Clone, Default)]
+struct ThrottleCounters {
+    nr_periods: u64,
+    nr_throttled: u64,
+    throttled_usec: u64,
+}
+
+/// cgroup v2 コレクター
+pub struct CgroupCollector {
+    cgroup: Cgroup,
+    last_throttle: Option<ThrottleCounters>,
+}
+
+impl CgroupCollector {
+    /// cgroup v2 が使える場合のみコレクターを作成
+    pub fn detect() -> Option<Self> {
+        Cgroup::detect().map(|cgroup| Self {
+            cgroup,
+            last_throttle: None,
+        })
+    }
+
+    /// cgroup統計を収集
+    pub fn collect(&mut self) -> Result<CgroupStats> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .context("Failed to get system time")?
+            .as_millis() as u64;
+
+        let throttle = ThrottleCounters {
+            nr_periods: self.cgroup.keyed_value("cpu.stat", "nr_periods"),
+            nr_throttled: self.cgroup.keyed_value("cpu.stat", "nr_throttled"),
+            throttled_usec: self.cgroup.keyed_value("cpu.stat", "throttled_usec"),
+        };
+
+        // 初回は差分が取れないので0
+        let delta = |get: fn(&ThrottleCounters) -> u64| {
+            self.last_throttle
+                .as_ref()
+                .map(|last| get(&throttle).saturating_sub(get(last)))
+                .unwrap_or(0)
+        };
+
+        let stats = CgroupStats {
+            time: now,
+            memory_current_mb: self.cgroup.memory_current().unwrap_or(0) / 1024 / 1024,
+            memory_max_mb: self.cgroup.memory_max().map(|max| max / 1024 / 1024),
+            oom: self.cgroup.keyed_value("memory.events", "oom"),
+            oom_kill: self.cgroup.keyed_value("memory.events", "oom_kill"),
+            cpu_quota_cores: self.cgroup.cpu_quota_cores(),
+            nr_periods: delta(|c| c.nr_periods),
+            nr_throttled: delta(|c| c.nr_throttled),
+            throttled_usec: delta(|c| c.throttled_usec),
+        };
+
+        // 次回のために現在値を保存
+        self.last_throttle = Some(throttle);
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_self_cgroup() {
+        let v2 = "0::/system.slice/actions.runner.service\n";
+        assert_eq!(
+            Cgroup::parse_proc_self_cgroup(v2).as_deref(),
+            Some("/system.slice/actions.runner.service")
+        );
+
+        let v1_only = "4:memory:/docker/abc\n1:cpu:/\n";
+        assert_eq!(Cgroup::parse_proc_self_cgroup(v1_only), None);
+    }
+
+    #[test]
+    fn test_parse_limits() {
+        assert_eq!(Cgroup::parse_max_value("max\n"), None);
+        assert_eq!(Cgroup::parse_max_value("4294967296\n"), Some(4294967296));
+
+        assert_eq!(Cgroup::parse_cpu_max("max 100000\n"), None);
+        assert_eq!(Cgroup::parse_cpu_max("200000 100000\n"), Some(2.0));
+    }
+
+    #[test]
+    fn test_parse_keyed_value() {
+        let events = "low 0\nhigh 0\nmax 12\noom 2\noom_kill 1\noom_group_kill 0\n";
+        assert_eq!(Cgroup::parse_keyed_value(events, "oom"), 2);
+        assert_eq!(Cgroup::parse_keyed_value(events, "oom_kill"), 1);
+        assert_eq!(Cgroup::parse_keyed_value(events, "missing"), 0);
+    }
+}
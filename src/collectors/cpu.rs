@@ -12,8 +12,23 @@ pub struct CpuStats {
     pub total_load: f64,
     /// ユーザーモードCPU使用率 (%)
     pub user_load: f64,
-    /// システムモードCPU使用率 (%)
+    /// システムモードCPU使用率 (%) (irq/softirqを含む)
     pub system_load: f64,
+    /// I/O待ちCPU使用率 (%) (total_load には含まれない)
+    #[serde(default)]
+    pub iowait_load: f64,
+    /// ハードウェア割り込み処理のCPU使用率 (%)
+    #[serde(default)]
+    pub irq_load: f64,
+    /// ソフトウェア割り込み処理のCPU使用率 (%)
+    #[serde(default)]
+    pub softirq_load: f64,
+    /// ハイパーバイザーに奪われたCPU時間の割合 (%)
+    #[serde(default)]
+    pub steal_load: f64,
+    /// コアごとのCPU使用率 (%) (cpu0, cpu1, ... の順)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub per_core: Vec<f64>,
 }
 
 /// /proc/stat から読み取った生のCPU時間
@@ -57,6 +72,7 @@ impl CpuTime {
 /// CPU使用率コレクター
 pub struct CpuCollector {
     last_cpu_time: Option<CpuTime>,
+    last_core_times: Vec<CpuTime>,
 }
 
 impl CpuCollector {
@@ -64,14 +80,19 @@ impl CpuCollector {
     pub fn new() -> Self {
         Self {
             last_cpu_time: None,
+            last_core_times: Vec::new(),
         }
     }
 
     /// CPU統計を収集
     pub fn collect(&mut self) -> Result<CpuStats> {
         let current_time = Self::current_timestamp_ms();
-        let current_cpu = Self::read_proc_stat()?;
+        let (current_cpu, current_cores) = Self::read_proc_stat()?;
+        Ok(self.usage(current_time, current_cpu, current_cores))
+    }
 
+    /// 前回のCPU時間との差分から使用率を計算
+    fn usage(&mut self, current_time: u64, current_cpu: CpuTime, current_cores: Vec<CpuTime>) -> CpuStats {
         let stats = if let Some(last_cpu) = &self.last_cpu_time {
             // 前回からの差分を計算
             let total_delta = current_cpu.total().saturating_sub(last_cpu.total());
@@ -97,11 +118,41 @@ impl CpuCollector {
                 0.0
             };
 
+            // 内訳ごとの使用率
+            let percent = |now: u64, before: u64| {
+                if total_delta > 0 {
+                    (now.saturating_sub(before) as f64 / total_delta as f64) * 100.0
+                } else {
+                    0.0
+                }
+            };
+            let iowait_load = percent(current_cpu.iowait, last_cpu.iowait);
+            let irq_load = percent(current_cpu.irq, last_cpu.irq);
+            let softirq_load = percent(current_cpu.softirq, last_cpu.softirq);
+            let steal_load = percent(current_cpu.steal, last_cpu.steal);
+
+            // コアごとの使用率 (前回存在しなかったコアは0)
+            let per_core = current_cores
+                .iter()
+                .enumerate()
+                .map(|(i, core)| {
+                    self.last_core_times
+                        .get(i)
+                        .map(|last_core| Self::active_percent(core, last_core))
+                        .unwrap_or(0.0)
+                })
+                .collect();
+
             CpuStats {
                 time: current_time,
                 total_load,
                 user_load,
                 system_load,
+                iowait_load,
+                irq_load,
+                softirq_load,
+                steal_load,
+                per_core,
             }
         } else {
             // 初回は0を返す
@@ -110,20 +161,37 @@ impl CpuCollector {
                 total_load: 0.0,
                 user_load: 0.0,
                 system_load: 0.0,
+                iowait_load: 0.0,
+                irq_load: 0.0,
+                softirq_load: 0.0,
+                steal_load: 0.0,
+                per_core: vec![0.0; current_cores.len()],
             }
         };
 
         // 次回のために現在値を保存
         self.last_cpu_time = Some(current_cpu);
+        self.last_core_times = current_cores;
 
-        Ok(stats)
+        stats
     }
 
-    /// /proc/stat を読み取り
-    fn read_proc_stat() -> Result<CpuTime> {
+    /// 2時点間のアクティブ時間の割合 (%) を計算
+    fn active_percent(current: &CpuTime, last: &CpuTime) -> f64 {
+        let total_delta = current.total().saturating_sub(last.total());
+        let active_delta = current.active().saturating_sub(last.active());
+        if total_delta > 0 {
+            (active_delta as f64 / total_delta as f64) * 100.0
+        } else {
+            0.0
+        }
+    }
+
+    /// /proc/stat を読み取り (全体の値とコアごとの値)
+    fn read_proc_stat() -> Result<(CpuTime, Vec<CpuTime>)> {
         let content = fs::read_to_string("/proc/stat")
             .context("Failed to read /proc/stat")?;
-        Self::parse_proc_stat(&content)
+        Ok((Self::parse_proc_stat(&content)?, Self::parse_proc_stat_cores(&content)?))
     }
 
     /// /proc/stat の内容をパース
@@ -139,6 +207,34 @@ impl CpuCollector {
             anyhow::bail!("Invalid /proc/stat format");
         }
 
+        Self::parse_cpu_fields(&parts)
+    }
+
+    /// /proc/stat の "cpuN ..." 行をコア番号順にパース
+    fn parse_proc_stat_cores(content: &str) -> Result<Vec<CpuTime>> {
+        let mut cores = Vec::new();
+
+        for line in content.lines().skip(1) {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let Some(index) = parts.first().and_then(|name| name.strip_prefix("cpu")) else {
+                // cpu行は先頭にまとまっているので、それ以外の行が来たら終了
+                break;
+            };
+            let index: usize = index.parse().context("Invalid cpu core index")?;
+            if parts.len() < 9 {
+                anyhow::bail!("Invalid /proc/stat format");
+            }
+            if cores.len() <= index {
+                cores.resize(index + 1, CpuTime::default());
+            }
+            cores[index] = Self::parse_cpu_fields(&parts)?;
+        }
+
+        Ok(cores)
+    }
+
+    /// "cpu" / "cpuN" 行の各フィールドを CpuTime に変換
+    fn parse_cpu_fields(parts: &[&str]) -> Result<CpuTime> {
         Ok(CpuTime {
             user: parts[1].parse().context("Invalid user time")?,
             nice: parts[2].parse().context("Invalid nice time")?,
@@ -199,4 +295,61 @@ mod tests {
         assert_eq!(cpu_time.system, 24433);
         assert_eq!(cpu_time.idle, 1117073);
     }
+
+    #[test]
+    fn test_parse_proc_stat_cores() {
+        let sample = "\
+cpu  300 0 100 600 0 0 0 0 0 0
+cpu0 100 0 50 350 0 0 0 0 0 0
+cpu1 200 0 50 250 0 0 0 0 0 0
+intr 65565 0 0
+ctxt 161018";
+        let cores = CpuCollector::parse_proc_stat_cores(sample).expect("Failed to parse");
+
+        assert_eq!(cores.len(), 2);
+        assert_eq!(cores[0].user, 100);
+        assert_eq!(cores[1].user, 200);
+        assert_eq!(cores[1].idle, 250);
+    }
+
+    #[test]
+    fn test_active_percent() {
+        let last = CpuTime { user: 100, idle: 100, ..Default::default() };
+        let current = CpuTime { user: 175, idle: 125, ..Default::default() };
+
+        assert_eq!(CpuCollector::active_percent(&current, &last), 75.0);
+        assert_eq!(CpuCollector::active_percent(&last, &last), 0.0);
+    }
+
+    #[test]
+    fn test_usage_by_mode() {
+        let last = CpuTime { user: 1000, system: 500, idle: 5000, ..Default::default() };
+        // 1000 tick のうち idle 400、iowait 100、それ以外が各モード
+        let current = CpuTime {
+            user: 1150,
+            nice: 50,
+            system: 600,
+            idle: 5400,
+            iowait: 100,
+            irq: 40,
+            softirq: 60,
+            steal: 100,
+        };
+
+        let mut collector = CpuCollector::new();
+        let first = collector.usage(1000, last.clone(), vec![last.clone()]);
+        assert_eq!((first.total_load, first.per_core.clone()), (0.0, vec![0.0]));
+
+        let stats = collector.usage(2000, current.clone(), vec![current]);
+        assert_eq!(stats.total_load, 50.0);
+        assert_eq!(stats.user_load, 20.0);
+        assert_eq!(stats.system_load, 20.0);
+        assert_eq!(stats.iowait_load, 10.0);
+        assert_eq!(stats.irq_load, 4.0);
+        assert_eq!(stats.softirq_load, 6.0);
+        assert_eq!(stats.steal_load, 10.0);
+        assert_eq!(stats.per_core, vec![50.0]);
+        // system は irq / softirq を含み、iowait は使用率に含まない
+        assert_eq!(stats.user_load + stats.system_load + stats.steal_load, stats.total_load);
+    }
 }
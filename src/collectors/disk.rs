@@ -0,0 +1,264 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// /proc/diskstats のセクタサイズ (カーネルは常に512バイト単位で報告する)
+const SECTOR_SIZE: u64 = 512;
+
+/// ディスクI/O統計データ
+#[derive(Debug, Clone, Serialize, serde::Deserialize)]
+pub struct DiskStats {
+    /// タイムスタンプ (ミリ秒)
+    pub time: u64,
+    /// ブロックデバイスごとの統計
+    pub devices: Vec<DiskDeviceStats>,
+}
+
+/// ブロックデバイス単位のI/O統計
+#[derive(Debug, Clone, Serialize, serde::Deserialize)]
+pub struct DiskDeviceStats {
+    /// デバイス名 (例: sda, nvme0n1)
+    pub name: String,
+    /// 読み込みスループット (bytes/s)
+    pub read_bytes_per_sec: f64,
+    /// 書き込みスループット (bytes/s)
+    pub write_bytes_per_sec: f64,
+    /// 読み込みIOPS
+    pub read_iops: f64,
+    /// 書き込みIOPS
+    pub write_iops: f64,
+    /// I/O処理中だった時間の割合 (%)
+    pub busy_percent: f64,
+}
+
+impl DiskStats {
+    /// 全デバイス合計の読み込みスループット (bytes/s)
+    pub fn total_read_bytes_per_sec(&self) -> f64 {
+        self.devices.iter().map(|d| d.read_bytes_per_sec).sum()
+    }
+
+    /// 全デバイス合計の書き込みスループット (bytes/s)
+    pub fn total_write_bytes_per_sec(&self) -> f64 {
+        self.devices.iter().map(|d| d.write_bytes_per_sec).sum()
+    }
+}
+
+/// /proc/diskstats から読み取った1デバイス分の生カウンタ
+#[derive(Debug, Clone, Default)]
+struct DiskCounters {
+    name: String,
+    reads_completed: u64,
+    sectors_read: u64,
+    writes_completed: u64,
+    sectors_written: u64,
+    io_ticks_ms: u64,
+}
+
+/// ディスクI/Oコレクター
+pub struct DiskCollector {
+    last_counters: Option<(u64, Vec<DiskCounters>)>,
+}
+
+impl DiskCollector {
+    /// 新しいコレクターを作成
+    pub fn new() -> Self {
+        Self {
+            last_counters: None,
+        }
+    }
+
+    /// ディスク統計を収集
+    pub fn collect(&mut self) -> Result<DiskStats> {
+        let current_time = Self::current_timestamp_ms();
+        let current = Self::read_proc_diskstats()?;
+        Ok(self.rates(current_time, current))
+    }
+
+    /// 前回のカウンタとの差分からレートを計算
+    fn rates(&mut self, current_time: u64, current: Vec<DiskCounters>) -> DiskStats {
+        let devices = current
+            .iter()
+            .map(|now| {
+                // 前回の値があるデバイスのみ差分を計算し、初回や新規デバイスは0を返す
+                let last = self.last_counters.as_ref().and_then(|(last_time, last)| {
+                    last.iter()
+                        .find(|d| d.name == now.name)
+                        .map(|d| (current_time.saturating_sub(*last_time), d))
+                });
+
+                match last {
+                    Some((elapsed_ms, before)) if elapsed_ms > 0 => {
+                        let elapsed_secs = elapsed_ms as f64 / 1000.0;
+                        let read_bytes = now.sectors_read.saturating_sub(before.sectors_read) * SECTOR_SIZE;
+                        let write_bytes = now.sectors_written.saturating_sub(before.sectors_written) * SECTOR_SIZE;
+                        let reads = now.reads_completed.saturating_sub(before.reads_completed);
+                        let writes = now.writes_completed.saturating_sub(before.writes_completed);
+                        let io_ticks = now.io_ticks_ms.saturating_sub(before.io_ticks_ms);
+
+                        DiskDeviceStats {
+                            name: now.name.clone(),
+                            read_bytes_per_sec: read_bytes as f64 / elapsed_secs,
+                            write_bytes_per_sec: write_bytes as f64 / elapsed_secs,
+                            read_iops: reads as f64 / elapsed_secs,
+                            write_iops: writes as f64 / elapsed_secs,
+                            busy_percent: ((io_ticks as f64 / elapsed_ms as f64) * 100.0).min(100.0),
+                        }
+                    }
+                    _ => DiskDeviceStats {
+                        name: now.name.clone(),
+                        read_bytes_per_sec: 0.0,
+                        write_bytes_per_sec: 0.0,
+                        read_iops: 0.0,
+                        write_iops: 0.0,
+                        busy_percent: 0.0,
+                    },
+                }
+            })
+            .collect();
+
+        // 次回のために現在値を保存
+        self.last_counters = Some((current_time, current));
+
+        DiskStats {
+            time: current_time,
+            devices,
+        }
+    }
+
+    /// /proc/diskstats を読み取り
+    fn read_proc_diskstats() -> Result<Vec<DiskCounters>> {
+        let content = fs::read_to_string("/proc/diskstats")
+            .context("Failed to read /proc/diskstats")?;
+        Self::parse_proc_diskstats(&content)
+    }
+
+    /// /proc/diskstats の内容をパース (ディスク全体のデバイスのみ)
+    ///
+    /// パーティションや dm / md デバイスのI/Oは下のディスクでも数えられるため、
+    /// 含めると合計が二重になる。
+    fn parse_proc_diskstats(content: &str) -> Result<Vec<DiskCounters>> {
+        // (メジャー番号, カウンタ)
+        let mut devices: Vec<(&str, DiskCounters)> = Vec::new();
+
+        for line in content.lines() {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.is_empty() {
+                continue;
+            }
+            if parts.len() < 14 {
+                anyhow::bail!("Invalid /proc/diskstats format");
+            }
+
+            let name = parts[2];
+            if Self::is_virtual_device(name) {
+                continue;
+            }
+
+            devices.push((parts[0], DiskCounters {
+                name: name.to_string(),
+                reads_completed: parts[3].parse().context("Invalid reads completed")?,
+                sectors_read: parts[5].parse().context("Invalid sectors read")?,
+                writes_completed: parts[7].parse().context("Invalid writes completed")?,
+                sectors_written: parts[9].parse().context("Invalid sectors written")?,
+                io_ticks_ms: parts[12].parse().context("Invalid io ticks")?,
+            }));
+        }
+
+        // パーティションはディスクと同じメジャー番号を持つ
+        let is_partition = |major: &str, name: &str| {
+            devices.iter().any(|(disk_major, disk)| *disk_major == major && Self::is_partition_of(name, &disk.name))
+        };
+        Ok(devices
+            .iter()
+            .filter(|(major, device)| !is_partition(major, &device.name))
+            .map(|(_, device)| device.clone())
+            .collect())
+    }
+
+    /// 集計対象外の仮想デバイス (dm / md は下のディスクと二重になる) か判定
+    fn is_virtual_device(name: &str) -> bool {
+        ["loop", "ram", "zram", "dm-", "md"].iter().any(|prefix| name.starts_with(prefix))
+    }
+
+    /// `name` が `disk` のパーティションの名前か判定 (`sda1`、`nvme0n1p1`、`mmcblk0p1` の形式)
+    fn is_partition_of(name: &str, disk: &str) -> bool {
+        let Some(suffix) = name.strip_prefix(disk) else {
+            return false;
+        };
+        // 数字で終わるディスク名には `p` を挟む
+        let number = if disk.ends_with(|c: char| c.is_ascii_digit()) {
+            suffix.strip_prefix('p').unwrap_or("")
+        } else {
+            suffix
+        };
+        !number.is_empty() && number.chars().all(|c| c.is_ascii_digit())
+    }
+
+    /// 現在のタイムスタンプ（ミリ秒）を取得
+    fn current_timestamp_ms() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis() as u64
+    }
+}
+
+impl Default for DiskCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_diskstats() {
+        let sample = "\
+   7       0 loop0 10 0 20 0 0 0 0 0 0 0 0 0 0 0 0 0 0
+   1       0 ram0 10 0 20 0 0 0 0 0 0 0 0 0 0 0 0 0 0
+ 254       0 vda 6211 4753 2039418 7007 1589 5431 860824 2616 0 2324 9707 404 0 57064 82 34 0
+ 254       1 vda1 6000 4700 2000000 7000 1500 5400 860000 2600 0 2300 9700 400 0 57000 80 30 0
+ 259       0 nvme0n1 100 0 800 0 10 0 80 0 0 5 0 0 0 0 0 0 0
+ 259       1 nvme0n1p1 90 0 700 0 9 0 70 0 0 4 0 0 0 0 0 0 0
+ 253       0 dm-0 80 0 600 0 8 0 60 0 0 4 0 0 0 0 0 0 0
+";
+        let devices = DiskCollector::parse_proc_diskstats(sample).expect("Failed to parse");
+
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].name, "vda");
+        assert_eq!(devices[0].reads_completed, 6211);
+        assert_eq!(devices[0].sectors_read, 2039418);
+        assert_eq!(devices[0].writes_completed, 1589);
+        assert_eq!(devices[0].sectors_written, 860824);
+        assert_eq!(devices[0].io_ticks_ms, 2324);
+        assert_eq!(devices[1].name, "nvme0n1");
+
+        assert!(DiskCollector::is_partition_of("sda12", "sda"));
+        assert!(DiskCollector::is_partition_of("mmcblk0p1", "mmcblk0"));
+        assert!(!DiskCollector::is_partition_of("mmcblk01", "mmcblk0"));
+        assert!(!DiskCollector::is_partition_of("sdaa", "sda"));
+    }
+
+    #[test]
+    fn test_total_excludes_partitions() {
+        let snapshot = |sectors: u64| {
+            let line = |major: u32, minor: u32, name: &str| {
+                format!("{} {} {} 0 0 {} 0 0 0 0 0 0 0 0 0 0 0 0 0 0\n", major, minor, name, sectors)
+            };
+            let content = [line(8, 0, "sda"), line(8, 1, "sda1"), line(253, 0, "dm-0"), line(8, 16, "sdb")].concat();
+            DiskCollector::parse_proc_diskstats(&content).unwrap()
+        };
+
+        let mut collector = DiskCollector::new();
+        collector.rates(1000, snapshot(0));
+        // 1秒で各デバイス 2048 セクタ (1 MiB)
+        let stats = collector.rates(2000, snapshot(2048));
+
+        let names: Vec<&str> = stats.devices.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["sda", "sdb"]);
+        assert_eq!(stats.total_read_bytes_per_sec(), 2.0 * 1024.0 * 1024.0);
+    }
+}
@@ -3,8 +3,10 @@ use serde::Serialize;
 use std::fs;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use super::cgroup::Cgroup;
+
 /// メモリ統計データ
-#[derive(Debug, Clone, Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, serde::Deserialize)]
 pub struct MemoryStats {
     /// タイムスタンプ (ミリ秒)
     pub time: u64,
@@ -14,13 +16,76 @@ pub struct MemoryStats {
     pub used_mb: u64,
     /// 総メモリ (MB)
     pub total_mb: u64,
+    /// cgroupのメモリ上限 (MB)。設定されている場合、使用量・使用率と以下の内訳は
+    /// cgroup基準 (memory.stat) になり、未使用メモリは上限までの残りになる
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit_mb: Option<u64>,
+    /// 未使用メモリ (MB) (MemFree)
+    #[serde(default)]
+    pub free_mb: u64,
+    /// ブロックデバイスのバッファ (MB) (Buffers)
+    #[serde(default)]
+    pub buffers_mb: u64,
+    /// ページキャッシュ (MB) (Cached、Shmemを含む)
+    #[serde(default)]
+    pub cached_mb: u64,
+    /// 回収可能なスラブ (MB) (SReclaimable)
+    #[serde(default)]
+    pub sreclaimable_mb: u64,
+    /// 共有メモリ・tmpfs (MB) (Shmem)
+    #[serde(default)]
+    pub shmem_mb: u64,
+    /// 書き戻し待ちのページ (MB) (Dirty)
+    #[serde(default)]
+    pub dirty_mb: u64,
+    /// 書き戻し中のページ (MB) (Writeback)
+    #[serde(default)]
+    pub writeback_mb: u64,
+    /// 総スワップ (MB) (SwapTotal)
+    #[serde(default)]
+    pub swap_total_mb: u64,
+    /// 使用中スワップ (MB) (SwapTotal - SwapFree)
+    #[serde(default)]
+    pub swap_used_mb: u64,
 }
 
-pub struct MemoryCollector;
+impl MemoryStats {
+    /// キャッシュ・バッファを除いたアプリケーションの使用量 (MB)
+    pub fn app_used_mb(&self) -> u64 {
+        self.limit_mb
+            .unwrap_or(self.total_mb)
+            .saturating_sub(self.free_mb)
+            .saturating_sub(self.buffers_mb)
+            .saturating_sub(self.cached_mb)
+            .saturating_sub(self.sreclaimable_mb)
+    }
+}
+
+/// /proc/meminfo から読み取った値 (kB)
+#[derive(Debug, Clone, Default)]
+struct MemInfo {
+    total: u64,
+    free: u64,
+    available: u64,
+    buffers: u64,
+    cached: u64,
+    sreclaimable: u64,
+    shmem: u64,
+    dirty: u64,
+    writeback: u64,
+    swap_total: u64,
+    swap_free: u64,
+}
+
+pub struct MemoryCollector {
+    cgroup: Option<Cgroup>,
+}
 
 impl MemoryCollector {
     pub fn new() -> Self {
-        Self
+        Self {
+            cgroup: Cgroup::detect(),
+        }
     }
 
     /// メモリ使用状況を取得
@@ -32,27 +97,26 @@ impl MemoryCollector {
 
         let content = fs::read_to_string("/proc/meminfo")
             .context("Failed to read /proc/meminfo")?;
+        let info = Self::parse_meminfo(&content);
 
-        let mut total_kb = 0u64;
-        let mut available_kb = 0u64;
+        // cgroupに実メモリより小さい上限があれば、そちらを基準にする
+        let cgroup = self.cgroup.as_ref().and_then(|cgroup| {
+            let max = cgroup.memory_max()?;
+            let current = cgroup.memory_current()?;
+            (max / 1024 < info.total).then(|| {
+                let stat = cgroup.memory_stat().unwrap_or_default();
+                Self::cgroup_meminfo(&info, max, current, &stat, cgroup.swap_current(), cgroup.swap_max())
+            })
+        });
 
-        for line in content.lines() {
-            if let Some(value) = line.strip_prefix("MemTotal:") {
-                total_kb = value.trim().split_whitespace().next()
-                    .and_then(|s| s.parse().ok())
-                    .unwrap_or(0);
-            } else if let Some(value) = line.strip_prefix("MemAvailable:") {
-                available_kb = value.trim().split_whitespace().next()
-                    .and_then(|s| s.parse().ok())
-                    .unwrap_or(0);
-            }
-        }
+        let total_mb = info.total / 1024;
+        // 内訳はホストと cgroup の値を混ぜない (cgroup の使用量はページキャッシュを含む)
+        let limit_mb = cgroup.as_ref().map(|cgroup| cgroup.total / 1024);
+        let info = cgroup.unwrap_or(info);
 
-        let used_kb = total_kb.saturating_sub(available_kb);
-        let total_mb = total_kb / 1024;
-        let used_mb = used_kb / 1024;
-        let usage_percent = if total_kb > 0 {
-            (used_kb as f64 / total_kb as f64) * 100.0
+        let used_kb = info.total.saturating_sub(info.available);
+        let usage_percent = if info.total > 0 {
+            (used_kb as f64 / info.total as f64) * 100.0
         } else {
             0.0
         };
@@ -60,10 +124,85 @@ impl MemoryCollector {
         Ok(MemoryStats {
             time: now,
             usage_percent,
-            used_mb,
+            used_mb: used_kb / 1024,
             total_mb,
+            limit_mb,
+            free_mb: info.free / 1024,
+            buffers_mb: info.buffers / 1024,
+            cached_mb: info.cached / 1024,
+            sreclaimable_mb: info.sreclaimable / 1024,
+            shmem_mb: info.shmem / 1024,
+            dirty_mb: info.dirty / 1024,
+            writeback_mb: info.writeback / 1024,
+            swap_total_mb: info.swap_total / 1024,
+            swap_used_mb: info.swap_total.saturating_sub(info.swap_free) / 1024,
         })
     }
+
+    /// cgroup の上限・使用量 (bytes) と memory.stat から、meminfo と同じ形の値 (kB) を作る
+    ///
+    /// `total` は上限、`free` は上限までの残りになる。`memory.current` はページキャッシュを含むので、
+    /// `available` はホストの MemAvailable と同じく回収できる `inactive_file` を空きとして数える
+    /// (docker / cAdvisor の working set と同じ)。バッファは memory.stat になく `file` に含まれる。
+    /// スワップの上限がなければホストのスワップ総量を上限とする。
+    fn cgroup_meminfo(
+        host: &MemInfo,
+        max: u64,
+        current: u64,
+        stat: &str,
+        swap_current: Option<u64>,
+        swap_max: Option<u64>,
+    ) -> MemInfo {
+        let stat_kb = |key: &str| Cgroup::parse_keyed_value(stat, key) / 1024;
+        let total = max / 1024;
+        let remaining = total.saturating_sub(current / 1024);
+        let working_set = (current / 1024).saturating_sub(stat_kb("inactive_file"));
+        let swap_total = swap_max.map_or(host.swap_total, |max| (max / 1024).min(host.swap_total));
+        MemInfo {
+            total,
+            free: remaining,
+            available: total.saturating_sub(working_set),
+            buffers: 0,
+            cached: stat_kb("file"),
+            sreclaimable: stat_kb("slab_reclaimable"),
+            shmem: stat_kb("shmem"),
+            dirty: stat_kb("file_dirty"),
+            writeback: stat_kb("file_writeback"),
+            swap_total,
+            swap_free: swap_total.saturating_sub(swap_current.unwrap_or(0) / 1024),
+        }
+    }
+
+    /// /proc/meminfo の内容をパース (欠けているフィールドは0)
+    fn parse_meminfo(content: &str) -> MemInfo {
+        let mut info = MemInfo::default();
+
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.split_whitespace().next()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0);
+
+            match key {
+                "MemTotal" => info.total = value,
+                "MemFree" => info.free = value,
+                "MemAvailable" => info.available = value,
+                "Buffers" => info.buffers = value,
+                "Cached" => info.cached = value,
+                "SReclaimable" => info.sreclaimable = value,
+                "Shmem" => info.shmem = value,
+                "Dirty" => info.dirty = value,
+                "Writeback" => info.writeback = value,
+                "SwapTotal" => info.swap_total = value,
+                "SwapFree" => info.swap_free = value,
+                _ => {}
+            }
+        }
+
+        info
+    }
 }
 
 #[cfg(test)]
@@ -74,7 +213,7 @@ mod tests {
     fn test_collect_memory() {
         let collector = MemoryCollector::new();
         let stats = collector.collect();
-        
+
         if let Ok(stats) = stats {
             assert!(stats.time > 0);
             assert!(stats.usage_percent >= 0.0);
@@ -82,4 +221,65 @@ mod tests {
             assert!(stats.total_mb > 0);
         }
     }
+
+    #[test]
+    fn test_parse_meminfo() {
+        let sample = "\
+MemTotal:        8000000 kB
+MemFree:         1000000 kB
+MemAvailable:    5000000 kB
+Buffers:          200000 kB
+Cached:          3000000 kB
+SwapCached:            0 kB
+Dirty:              1024 kB
+Writeback:             0 kB
+Shmem:            100000 kB
+SReclaimable:     400000 kB
+SwapTotal:       4000000 kB
+SwapFree:        3000000 kB
+";
+        let info = MemoryCollector::parse_meminfo(sample);
+
+        assert_eq!(info.total, 8000000);
+        assert_eq!(info.free, 1000000);
+        assert_eq!(info.available, 5000000);
+        assert_eq!(info.buffers, 200000);
+        assert_eq!(info.cached, 3000000);
+        assert_eq!(info.sreclaimable, 400000);
+        assert_eq!(info.shmem, 100000);
+        assert_eq!(info.dirty, 1024);
+        assert_eq!(info.writeback, 0);
+        assert_eq!(info.swap_total, 4000000);
+        assert_eq!(info.swap_free, 3000000);
+    }
+
+    #[test]
+    fn test_cgroup_meminfo() {
+        const MB: u64 = 1024 * 1024;
+        let host = MemInfo { total: 16 * 1024 * 1024, cached: 8 * 1024 * 1024, swap_total: 4 * 1024 * 1024, ..Default::default() };
+        let stat = "anon 1073741824\nfile 536870912\nshmem 1048576\nslab_reclaimable 104857600\nfile_dirty 4096\ninactive_file 314572800\n";
+        let info = MemoryCollector::cgroup_meminfo(&host, 4096 * MB, 1700 * MB, stat, Some(10 * MB), None);
+
+        assert_eq!(info.total / 1024, 4096);
+        assert_eq!(info.free / 1024, 2396);
+        // 使用量は回収できる inactive_file を除いた working set (1700 - 300 MB)
+        assert_eq!((info.total - info.available) / 1024, 1400);
+        // ホストのページキャッシュではなく cgroup の file を使う
+        assert_eq!(info.cached / 1024, 512);
+        assert_eq!(info.sreclaimable / 1024, 100);
+        assert_eq!(info.dirty, 4);
+        assert_eq!(info.swap_total / 1024, 4096);
+        assert_eq!((info.swap_total - info.swap_free) / 1024, 10);
+
+        // 積み上げの合計は cgroup の使用量になる
+        let stats = MemoryStats {
+            total_mb: host.total / 1024,
+            limit_mb: Some(info.total / 1024),
+            free_mb: info.free / 1024,
+            cached_mb: info.cached / 1024,
+            sreclaimable_mb: info.sreclaimable / 1024,
+            ..Default::default()
+        };
+        assert_eq!(stats.app_used_mb() + stats.cached_mb + stats.sreclaimable_mb, 1700);
+    }
 }
@@ -1,5 +1,17 @@
+pub mod cgroup;
 pub mod cpu;
+pub mod disk;
 pub mod memory;
+pub mod network;
+pub mod pressure;
+pub mod process;
+pub mod tracer;
 
+pub use cgroup::{CgroupCollector, CgroupStats};
 pub use cpu::{CpuCollector, CpuStats};
+pub use disk::{DiskCollector, DiskStats};
 pub use memory::{MemoryCollector, MemoryStats};
+pub use network::{NetworkCollector, NetworkStats};
+pub use pressure::{PressureCollector, PressureStats};
+pub use process::{ProcessCollector, ProcessStats};
+pub use tracer::{ProcessTracer, TraceEvent, TracedProcess};
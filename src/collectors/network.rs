@@ -0,0 +1,202 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// ネットワークI/O統計データ
+#[derive(Debug, Clone, Serialize, serde::Deserialize)]
+pub struct NetworkStats {
+    /// タイムスタンプ (ミリ秒)
+    pub time: u64,
+    /// 受信スループット (bytes/s)
+    pub rx_bytes_per_sec: f64,
+    /// 送信スループット (bytes/s)
+    pub tx_bytes_per_sec: f64,
+    /// 受信パケット数 (packets/s)
+    pub rx_packets_per_sec: f64,
+    /// 送信パケット数 (packets/s)
+    pub tx_packets_per_sec: f64,
+    /// 前回からの受信エラー数
+    pub rx_errors: u64,
+    /// 前回からの送信エラー数
+    pub tx_errors: u64,
+    /// 前回からの受信ドロップ数
+    pub rx_drops: u64,
+    /// 前回からの送信ドロップ数
+    pub tx_drops: u64,
+}
+
+/// /proc/net/dev から読み取った生のカウンタ (全インターフェース合計)
+#[derive(Debug, Clone, Default)]
+struct NetCounters {
+    rx_bytes: u64,
+    rx_packets: u64,
+    rx_errors: u64,
+    rx_drops: u64,
+    tx_bytes: u64,
+    tx_packets: u64,
+    tx_errors: u64,
+    tx_drops: u64,
+}
+
+/// ネットワークI/Oコレクター
+pub struct NetworkCollector {
+    last_counters: Option<(u64, NetCounters)>,
+}
+
+impl NetworkCollector {
+    /// 新しいコレクターを作成
+    pub fn new() -> Self {
+        Self {
+            last_counters: None,
+        }
+    }
+
+    /// ネットワーク統計を収集
+    pub fn collect(&mut self) -> Result<NetworkStats> {
+        let current_time = Self::current_timestamp_ms();
+        let current = Self::read_proc_net_dev()?;
+
+        let stats = if let Some((last_time, last)) = &self.last_counters {
+            // 前回からの差分を計算
+            let elapsed_secs = current_time.saturating_sub(*last_time) as f64 / 1000.0;
+            let rate = |now: u64, before: u64| {
+                if elapsed_secs > 0.0 {
+                    now.saturating_sub(before) as f64 / elapsed_secs
+                } else {
+                    0.0
+                }
+            };
+
+            NetworkStats {
+                time: current_time,
+                rx_bytes_per_sec: rate(current.rx_bytes, last.rx_bytes),
+                tx_bytes_per_sec: rate(current.tx_bytes, last.tx_bytes),
+                rx_packets_per_sec: rate(current.rx_packets, last.rx_packets),
+                tx_packets_per_sec: rate(current.tx_packets, last.tx_packets),
+                rx_errors: current.rx_errors.saturating_sub(last.rx_errors),
+                tx_errors: current.tx_errors.saturating_sub(last.tx_errors),
+                rx_drops: current.rx_drops.saturating_sub(last.rx_drops),
+                tx_drops: current.tx_drops.saturating_sub(last.tx_drops),
+            }
+        } else {
+            // 初回は0を返す
+            NetworkStats {
+                time: current_time,
+                rx_bytes_per_sec: 0.0,
+                tx_bytes_per_sec: 0.0,
+                rx_packets_per_sec: 0.0,
+                tx_packets_per_sec: 0.0,
+                rx_errors: 0,
+                tx_errors: 0,
+                rx_drops: 0,
+                tx_drops: 0,
+            }
+        };
+
+        // 次回のために現在値を保存
+        self.last_counters = Some((current_time, current));
+
+        Ok(stats)
+    }
+
+    /// /proc/net/dev を読み取り
+    fn read_proc_net_dev() -> Result<NetCounters> {
+        let content = fs::read_to_string("/proc/net/dev")
+            .context("Failed to read /proc/net/dev")?;
+        Self::parse_proc_net_dev(&content)
+    }
+
+    /// /proc/net/dev の内容をパース (ループバックと仮想インターフェースを除く全インターフェースを合算)
+    fn parse_proc_net_dev(content: &str) -> Result<NetCounters> {
+        let mut counters = NetCounters::default();
+
+        // 先頭2行はヘッダー
+        for line in content.lines().skip(2) {
+            let Some((iface, values)) = line.split_once(':') else {
+                continue;
+            };
+            if Self::is_virtual_interface(iface.trim()) {
+                continue;
+            }
+
+            let fields: Vec<u64> = values
+                .split_whitespace()
+                .map(|s| s.parse().context("Invalid /proc/net/dev counter"))
+                .collect::<Result<_>>()?;
+            if fields.len() < 12 {
+                anyhow::bail!("Invalid /proc/net/dev format");
+            }
+
+            counters.rx_bytes += fields[0];
+            counters.rx_packets += fields[1];
+            counters.rx_errors += fields[2];
+            counters.rx_drops += fields[3];
+            counters.tx_bytes += fields[8];
+            counters.tx_packets += fields[9];
+            counters.tx_errors += fields[10];
+            counters.tx_drops += fields[11];
+        }
+
+        Ok(counters)
+    }
+
+    /// 集計対象外の仮想インターフェースか判定
+    ///
+    /// コンテナのブリッジや veth を通る通信は物理NICでも数えられるので、足すと二重になる。
+    /// コンテナの中では `eth0` 自体が veth だが、名前が `eth0` なので集計される。
+    fn is_virtual_interface(name: &str) -> bool {
+        name == "lo"
+            || ["docker", "veth", "br-", "virbr", "cni", "flannel", "cali", "vxlan", "tun", "tap"]
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+    }
+
+    /// 現在のタイムスタンプ（ミリ秒）を取得
+    fn current_timestamp_ms() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis() as u64
+    }
+}
+
+impl Default for NetworkCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_net_dev() {
+        let sample = "\
+Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo: 5641274    1172    0    0    0     0          0         0  5641274    1172    0    0    0     0       0          0
+  eth0:  397886      84    1    2    0     0          0         0    10125      82    3    4    0     0       0          0
+  eth1:    1000      10    0    0    0     0          0         0     2000      20    0    0    0     0       0          0
+docker0:   50000     500    0    0    0     0          0         0    60000     600    0    0    0     0       0          0
+vethab12cd3:   50000     500    0    0    0     0          0         0    60000     600    0    0    0     0       0          0
+br-0123abcd:   50000     500    0    0    0     0          0         0    60000     600    0    0    0     0       0          0
+";
+        let counters = NetworkCollector::parse_proc_net_dev(sample).expect("Failed to parse");
+
+        assert_eq!(counters.rx_bytes, 398886);
+        assert_eq!(counters.rx_packets, 94);
+        assert_eq!(counters.rx_errors, 1);
+        assert_eq!(counters.rx_drops, 2);
+        assert_eq!(counters.tx_bytes, 12125);
+        assert_eq!(counters.tx_packets, 102);
+        assert_eq!(counters.tx_errors, 3);
+        assert_eq!(counters.tx_drops, 4);
+
+        assert!(NetworkCollector::is_virtual_interface("docker0"));
+        assert!(NetworkCollector::is_virtual_interface("veth1a2b3c"));
+        assert!(!NetworkCollector::is_virtual_interface("eth0"));
+        assert!(!NetworkCollector::is_virtual_interface("ens5"));
+    }
+}
@@ -0,0 +1,172 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Pressure Stall Information (PSI) 統計データ
+#[derive(Debug, Clone, Serialize, serde::Deserialize)]
+pub struct PressureStats {
+    /// タイムスタンプ (ミリ秒)
+    pub time: u64,
+    /// CPU待ちによるストール
+    pub cpu: ResourcePressure,
+    /// メモリ不足によるストール
+    pub memory: ResourcePressure,
+    /// I/O待ちによるストール
+    pub io: ResourcePressure,
+}
+
+/// 1リソース分のPSI値
+#[derive(Debug, Clone, Default, Serialize, serde::Deserialize)]
+pub struct ResourcePressure {
+    /// 一部のタスクがストールしていた時間の割合 (直近10秒平均, %)
+    pub some_avg10: f64,
+    /// 全タスクがストールしていた時間の割合 (直近10秒平均, %)
+    pub full_avg10: f64,
+    /// 前回からの some ストール時間 (マイクロ秒)
+    pub some_stall_us: u64,
+    /// 前回からの full ストール時間 (マイクロ秒)
+    pub full_stall_us: u64,
+}
+
+/// /proc/pressure/* から読み取った生の値
+#[derive(Debug, Clone, Default)]
+struct PressureCounters {
+    some_avg10: f64,
+    full_avg10: f64,
+    some_total: u64,
+    full_total: u64,
+}
+
+/// PSIコレクター
+pub struct PressureCollector {
+    last_counters: Option<[PressureCounters; 3]>,
+}
+
+impl PressureCollector {
+    /// 新しいコレクターを作成
+    pub fn new() -> Self {
+        Self {
+            last_counters: None,
+        }
+    }
+
+    /// PSI統計を収集
+    ///
+    /// PSIが無効なカーネルではファイルが存在しないため、その場合は0を返す
+    pub fn collect(&mut self) -> Result<PressureStats> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .context("Failed to get system time")?
+            .as_millis() as u64;
+
+        let current = [
+            Self::read_pressure("cpu"),
+            Self::read_pressure("memory"),
+            Self::read_pressure("io"),
+        ];
+
+        let [cpu, memory, io] = std::array::from_fn(|i| {
+            let now = &current[i];
+            let last = self.last_counters.as_ref().map(|last| &last[i]);
+            ResourcePressure {
+                some_avg10: now.some_avg10,
+                full_avg10: now.full_avg10,
+                // 初回は差分が取れないので0
+                some_stall_us: last
+                    .map(|l| now.some_total.saturating_sub(l.some_total))
+                    .unwrap_or(0),
+                full_stall_us: last
+                    .map(|l| now.full_total.saturating_sub(l.full_total))
+                    .unwrap_or(0),
+            }
+        });
+
+        // 次回のために現在値を保存
+        self.last_counters = Some(current);
+
+        Ok(PressureStats {
+            time: now,
+            cpu,
+            memory,
+            io,
+        })
+    }
+
+    /// /proc/pressure/<resource> を読み取り (読めない場合は0)
+    fn read_pressure(resource: &str) -> PressureCounters {
+        fs::read_to_string(format!("/proc/pressure/{}", resource))
+            .map(|content| Self::parse_pressure(&content))
+            .unwrap_or_default()
+    }
+
+    /// /proc/pressure/* の内容をパース (欠けているフィールドは0)
+    fn parse_pressure(content: &str) -> PressureCounters {
+        let mut counters = PressureCounters::default();
+
+        for line in content.lines() {
+            let mut parts = line.split_whitespace();
+            let kind = parts.next();
+
+            let mut avg10 = 0.0;
+            let mut total = 0;
+            for field in parts {
+                if let Some(value) = field.strip_prefix("avg10=") {
+                    avg10 = value.parse().unwrap_or(0.0);
+                } else if let Some(value) = field.strip_prefix("total=") {
+                    total = value.parse().unwrap_or(0);
+                }
+            }
+
+            match kind {
+                Some("some") => {
+                    counters.some_avg10 = avg10;
+                    counters.some_total = total;
+                }
+                Some("full") => {
+                    counters.full_avg10 = avg10;
+                    counters.full_total = total;
+                }
+                _ => {}
+            }
+        }
+
+        counters
+    }
+}
+
+impl Default for PressureCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pressure() {
+        let sample = "\
+some avg10=2.89 avg60=2.33 avg300=2.17 total=19457539
+full avg10=0.50 avg60=0.00 avg300=0.00 total=1200
+";
+        let counters = PressureCollector::parse_pressure(sample);
+
+        assert_eq!(counters.some_avg10, 2.89);
+        assert_eq!(counters.some_total, 19457539);
+        assert_eq!(counters.full_avg10, 0.50);
+        assert_eq!(counters.full_total, 1200);
+    }
+
+    #[test]
+    fn test_parse_pressure_without_full_line() {
+        // 古いカーネルの /proc/pressure/cpu には full 行がない
+        let sample = "some avg10=1.00 avg60=0.00 avg300=0.00 total=42\n";
+        let counters = PressureCollector::parse_pressure(sample);
+
+        assert_eq!(counters.some_total, 42);
+        assert_eq!(counters.full_avg10, 0.0);
+        assert_eq!(counters.full_total, 0);
+    }
+}
@@ -0,0 +1,259 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::redact::Redactor;
+
+/// コマンドラインの最大文字数 (これを超える分は切り詰める)
+const MAX_CMDLINE_CHARS: usize = 120;
+
+/// プロセス統計データ (1回のサンプリング分)
+#[derive(Debug, Clone, Serialize, serde::Deserialize)]
+pub struct ProcessStats {
+    /// タイムスタンプ (ミリ秒)
+    pub time: u64,
+    /// CPU使用率の上位プロセス
+    pub top_cpu: Vec<ProcessInfo>,
+    /// 常駐メモリ (RSS) の上位プロセス
+    pub top_memory: Vec<ProcessInfo>,
+}
+
+/// 1プロセス分の情報
+#[derive(Debug, Clone, Serialize, serde::Deserialize)]
+pub struct ProcessInfo {
+    /// プロセスID
+    pub pid: u32,
+    /// 親プロセスID
+    pub ppid: u32,
+    /// 起動時刻 (/proc/[pid]/stat の starttime、システム起動からの clock ticks)
+    ///
+    /// 再利用された PID を別のプロセスとして見分けるために使う。
+    #[serde(default)]
+    pub start_time: u64,
+    /// 実行ファイル名 (/proc/[pid]/stat の comm)
+    pub comm: String,
+    /// コマンドライン (機密情報マスク・切り詰め済み)
+    pub cmdline: String,
+    /// CPU使用率 (%) (1コアを使い切ると100%)
+    pub cpu_percent: f64,
+    /// 常駐メモリ (MB)
+    pub rss_mb: u64,
+}
+
+/// /proc/[pid]/stat から読み取った値
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ProcStat {
+    pub(crate) ppid: u32,
+    pub(crate) comm: String,
+    /// utime + stime (clock ticks)
+    pub(crate) cpu_ticks: u64,
+    /// 起動時刻 (システム起動からの clock ticks)。PIDの再利用を見分けるために使う
+    pub(crate) start_time: u64,
+}
+
+/// プロセスコレクター
+pub struct ProcessCollector {
+    top_n: usize,
+    redactor: Redactor,
+    clock_ticks_per_sec: f64,
+    page_size: u64,
+    /// 前回のサンプリング時刻
+    last_time: Option<u64>,
+    /// 前回のPIDごとの (起動時刻, CPU時間)
+    last_ticks: HashMap<u32, (u64, u64)>,
+}
+
+impl ProcessCollector {
+    /// 上位 `top_n` プロセスを記録するコレクターを作成
+    pub fn new(top_n: usize, redactor: Redactor) -> Self {
+        // SAFETY: sysconf は引数の定数を読むだけで副作用がない
+        let (clock_ticks, page_size) = unsafe {
+            (libc::sysconf(libc::_SC_CLK_TCK), libc::sysconf(libc::_SC_PAGESIZE))
+        };
+
+        Self {
+            top_n,
+            redactor,
+            clock_ticks_per_sec: if clock_ticks > 0 { clock_ticks as f64 } else { 100.0 },
+            page_size: if page_size > 0 { page_size as u64 } else { 4096 },
+            last_time: None,
+            last_ticks: HashMap::new(),
+        }
+    }
+
+    /// プロセス統計を収集
+    pub fn collect(&mut self) -> Result<ProcessStats> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .context("Failed to get system time")?
+            .as_millis() as u64;
+
+        let entries = fs::read_dir("/proc").context("Failed to read /proc")?;
+
+        let elapsed_secs = self.last_time
+            .map(|last_time| now.saturating_sub(last_time) as f64 / 1000.0)
+            .unwrap_or(0.0);
+
+        let mut processes = Vec::new();
+        let mut current_ticks = HashMap::new();
+
+        for entry in entries.flatten() {
+            let Some(pid) = entry.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) else {
+                continue;
+            };
+
+            // スキャン中に終了したプロセスは読めないのでスキップ
+            let Ok(stat_content) = fs::read_to_string(format!("/proc/{}/stat", pid)) else {
+                continue;
+            };
+            let Some(stat) = Self::parse_stat(&stat_content) else {
+                continue;
+            };
+            let rss_pages = fs::read_to_string(format!("/proc/{}/statm", pid))
+                .ok()
+                .and_then(|content| Self::parse_statm_resident(&content))
+                .unwrap_or(0);
+            let cmdline = fs::read(format!("/proc/{}/cmdline", pid))
+                .map(|raw| Self::format_cmdline(&raw, &self.redactor))
+                .unwrap_or_default();
+
+            // 前回と同じプロセス (起動時刻が一致) であれば差分からCPU使用率を計算
+            let cpu_percent = self.last_ticks
+                .get(&pid)
+                .filter(|(start_time, _)| *start_time == stat.start_time)
+                .filter(|_| elapsed_secs > 0.0)
+                .map(|(_, ticks)| {
+                    let delta = stat.cpu_ticks.saturating_sub(*ticks) as f64;
+                    delta / self.clock_ticks_per_sec / elapsed_secs * 100.0
+                })
+                .unwrap_or(0.0);
+
+            current_ticks.insert(pid, (stat.start_time, stat.cpu_ticks));
+            processes.push(ProcessInfo {
+                pid,
+                ppid: stat.ppid,
+                start_time: stat.start_time,
+                comm: stat.comm,
+                cmdline,
+                cpu_percent,
+                rss_mb: rss_pages * self.page_size / 1024 / 1024,
+            });
+        }
+
+        // 次回のために現在値を保存
+        self.last_time = Some(now);
+        self.last_ticks = current_ticks;
+
+        Ok(ProcessStats {
+            time: now,
+            top_cpu: Self::top_by(&processes, self.top_n, |p| p.cpu_percent),
+            top_memory: Self::top_by(&processes, self.top_n, |p| p.rss_mb as f64),
+        })
+    }
+
+    /// 指定したキーで降順に並べた上位 `n` 件
+    fn top_by(processes: &[ProcessInfo], n: usize, key: fn(&ProcessInfo) -> f64) -> Vec<ProcessInfo> {
+        let mut sorted: Vec<&ProcessInfo> = processes.iter().collect();
+        sorted.sort_by(|a, b| key(b).total_cmp(&key(a)));
+        sorted.into_iter().take(n).cloned().collect()
+    }
+
+    /// /proc/[pid]/stat の内容をパース
+    pub(crate) fn parse_stat(content: &str) -> Option<ProcStat> {
+        // comm は括弧で囲まれ、空白や括弧を含みうるので最後の ')' で区切る
+        let open = content.find('(')?;
+        let close = content.rfind(')')?;
+        let comm = content.get(open + 1..close)?.to_string();
+
+        // fields[0] が3番目のフィールド (state)
+        let fields: Vec<&str> = content.get(close + 1..)?.split_whitespace().collect();
+        if fields.len() < 20 {
+            return None;
+        }
+
+        let utime: u64 = fields[11].parse().ok()?;
+        let stime: u64 = fields[12].parse().ok()?;
+
+        Some(ProcStat {
+            ppid: fields[1].parse().ok()?,
+            comm,
+            cpu_ticks: utime + stime,
+            start_time: fields[19].parse().ok()?,
+        })
+    }
+
+    /// /proc/[pid]/statm から常駐ページ数を取得
+    fn parse_statm_resident(content: &str) -> Option<u64> {
+        content.split_whitespace().nth(1)?.parse().ok()
+    }
+
+    /// NUL区切りの /proc/[pid]/cmdline を機密情報をマスクしてから空白区切りにして切り詰める
+    pub(super) fn format_cmdline(raw: &[u8], redactor: &Redactor) -> String {
+        let raw = String::from_utf8_lossy(raw);
+        let args: Vec<&str> = raw.split('\0').filter(|arg| !arg.is_empty()).collect();
+        let cmdline = redactor.redact_args(&args).join(" ");
+
+        if cmdline.chars().count() > MAX_CMDLINE_CHARS {
+            let truncated: String = cmdline.chars().take(MAX_CMDLINE_CHARS).collect();
+            format!("{}…", truncated)
+        } else {
+            cmdline
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stat() {
+        let sample = "1234 (rustc (worker)) R 1200 1234 1200 0 -1 4194560 5000 0 0 0 250 50 0 0 20 0 8 0 98765 1000000 2000 18446744073709551615";
+        let stat = ProcessCollector::parse_stat(sample).expect("Failed to parse");
+
+        assert_eq!(stat.ppid, 1200);
+        assert_eq!(stat.comm, "rustc (worker)");
+        assert_eq!(stat.cpu_ticks, 300);
+        assert_eq!(stat.start_time, 98765);
+    }
+
+    #[test]
+    fn test_format_cmdline() {
+        let redactor = Redactor::default();
+        assert_eq!(
+            ProcessCollector::format_cmdline(b"cargo\0build\0--release\0", &redactor),
+            "cargo build --release"
+        );
+        assert_eq!(
+            ProcessCollector::format_cmdline(b"gh\0--token\0ghp_abc\0", &redactor),
+            "gh --token ***"
+        );
+
+        let long = "x".repeat(MAX_CMDLINE_CHARS + 10);
+        let formatted = ProcessCollector::format_cmdline(long.as_bytes(), &redactor);
+        assert_eq!(formatted.chars().count(), MAX_CMDLINE_CHARS + 1);
+        assert!(formatted.ends_with('…'));
+    }
+
+    #[test]
+    fn test_top_by() {
+        let process = |pid: u32, cpu_percent: f64, rss_mb: u64| ProcessInfo {
+            pid,
+            ppid: 1,
+            start_time: 0,
+            comm: format!("p{}", pid),
+            cmdline: String::new(),
+            cpu_percent,
+            rss_mb,
+        };
+        let processes = vec![process(1, 10.0, 300), process(2, 90.0, 100), process(3, 50.0, 200)];
+
+        let top_cpu = ProcessCollector::top_by(&processes, 2, |p| p.cpu_percent);
+        assert_eq!(top_cpu.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![2, 3]);
+
+        let top_memory = ProcessCollector::top_by(&processes, 2, |p| p.rss_mb as f64);
+        assert_eq!(top_memory.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![1, 3]);
+    }
+}
@@ -0,0 +1,191 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::process::ProcessCollector;
+use crate::redact::Redactor;
+
+/// トレースされたプロセス1つ分の記録
+#[derive(Debug, Clone, Serialize, serde::Deserialize)]
+pub struct TracedProcess {
+    /// プロセスID
+    pub pid: u32,
+    /// 親プロセスID
+    pub ppid: u32,
+    /// 実行ファイル名
+    pub comm: String,
+    /// コマンドライン (機密情報マスク・切り詰め済み)
+    pub cmdline: String,
+    /// 起動時刻 (ミリ秒)
+    pub start_time: u64,
+    /// 終了を検出した時刻 (ミリ秒)。トレース終了時点で実行中ならNone
+    pub end_time: Option<u64>,
+}
+
+/// トレーサーが検出したイベント
+#[derive(Debug, Clone, Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceEvent {
+    /// プロセスの起動を検出
+    Started(TracedProcess),
+    /// プロセスの終了を検出
+    Exited {
+        pid: u32,
+        /// 起動時刻 (ミリ秒)。PIDの再利用と区別するために使う
+        start_time: u64,
+        end_time: u64,
+    },
+}
+
+/// /proc をポーリングしてプロセスの起動・終了を検出するトレーサー
+///
+/// ポーリング間隔より短命なプロセスは検出できない。終了時刻は
+/// プロセスが /proc から消えたのを検出した時刻になる。
+pub struct ProcessTracer {
+    /// トレース開始時点の起動からの経過時間 (clock ticks)。これより前に起動したプロセスは記録しない
+    started_ticks: u64,
+    /// システム起動時刻 (ミリ秒)
+    boot_time_ms: u64,
+    clock_ticks_per_sec: u64,
+    redactor: Redactor,
+    /// 実行中のプロセス: (pid, 起動時刻 ticks) → 起動時刻 (ミリ秒)
+    running: HashMap<(u32, u64), u64>,
+}
+
+impl ProcessTracer {
+    /// 新しいトレーサーを作成
+    pub fn new(redactor: Redactor) -> Result<Self> {
+        let uptime = fs::read_to_string("/proc/uptime").context("Failed to read /proc/uptime")?;
+        let uptime_secs = Self::parse_uptime(&uptime).context("Invalid /proc/uptime format")?;
+
+        // SAFETY: sysconf は引数の定数を読むだけで副作用がない
+        let clock_ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        let clock_ticks_per_sec = if clock_ticks > 0 { clock_ticks as u64 } else { 100 };
+
+        // btime は秒単位なので、起動時刻は uptime から逆算する
+        Ok(Self {
+            started_ticks: (uptime_secs * clock_ticks_per_sec as f64) as u64,
+            boot_time_ms: Self::current_timestamp_ms().saturating_sub((uptime_secs * 1000.0) as u64),
+            clock_ticks_per_sec,
+            redactor,
+            running: HashMap::new(),
+        })
+    }
+
+    /// /proc をスキャンして前回からの起動・終了イベントを返す
+    pub fn poll(&mut self) -> Result<Vec<TraceEvent>> {
+        let now = Self::current_timestamp_ms();
+        let entries = fs::read_dir("/proc").context("Failed to read /proc")?;
+
+        let mut events = Vec::new();
+        let mut seen = HashMap::new();
+        for entry in entries.flatten() {
+            let Some(pid) = entry.file_name().to_str().and_then(|s| s.parse::<u32>().ok()) else {
+                continue;
+            };
+            let Ok(stat_content) = fs::read_to_string(format!("/proc/{}/stat", pid)) else {
+                continue;
+            };
+            let Some(stat) = ProcessCollector::parse_stat(&stat_content) else {
+                continue;
+            };
+
+            let key = (pid, stat.start_time);
+            if let Some(&start_time) = self.running.get(&key) {
+                seen.insert(key, start_time);
+                continue;
+            }
+
+            // トレース開始前から動いているプロセスと自分自身は対象外
+            if stat.start_time < self.started_ticks || pid == std::process::id() {
+                continue;
+            }
+            let start_time = self.boot_time_ms + stat.start_time * 1000 / self.clock_ticks_per_sec;
+
+            let cmdline = fs::read(format!("/proc/{}/cmdline", pid))
+                .map(|raw| ProcessCollector::format_cmdline(&raw, &self.redactor))
+                .unwrap_or_default();
+
+            events.push(TraceEvent::Started(TracedProcess {
+                pid,
+                ppid: stat.ppid,
+                comm: stat.comm,
+                cmdline,
+                start_time,
+                end_time: None,
+            }));
+            seen.insert(key, start_time);
+        }
+
+        // 前回あって今回ないプロセスは終了したとみなす
+        for (key, &start_time) in &self.running {
+            if !seen.contains_key(key) {
+                events.push(TraceEvent::Exited {
+                    pid: key.0,
+                    start_time,
+                    end_time: now,
+                });
+            }
+        }
+        self.running = seen;
+
+        Ok(events)
+    }
+
+    /// /proc/uptime からシステム起動後の経過秒数を取得
+    fn parse_uptime(content: &str) -> Option<f64> {
+        content.split_whitespace().next()?.parse().ok()
+    }
+
+    /// 現在のタイムスタンプ（ミリ秒）を取得
+    fn current_timestamp_ms() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time went backwards")
+            .as_millis() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_uptime() {
+        assert_eq!(ProcessTracer::parse_uptime("3520.48 3301.02\n"), Some(3520.48));
+        assert_eq!(ProcessTracer::parse_uptime(""), None);
+    }
+
+    #[test]
+    fn test_poll_detects_child_process() {
+        let mut tracer = ProcessTracer::new(Redactor::default()).expect("Failed to create tracer");
+        tracer.poll().expect("Failed to poll");
+
+        let mut child = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .expect("Failed to spawn sleep");
+        let pid = child.id();
+        let started = tracer.poll().expect("Failed to poll");
+
+        child.kill().expect("Failed to kill sleep");
+        child.wait().expect("Failed to wait sleep");
+        let exited = tracer.poll().expect("Failed to poll");
+
+        let traced = started.iter()
+            .find_map(|event| match event {
+                TraceEvent::Started(p) if p.pid == pid => Some(p),
+                _ => None,
+            })
+            .expect("Child process start was not traced");
+        assert_eq!(traced.comm, "sleep");
+        assert_eq!(traced.ppid, std::process::id());
+        assert!(exited.iter().any(|event| matches!(
+            event,
+            TraceEvent::Exited { pid: exited_pid, start_time, .. }
+                if *exited_pid == pid && *start_time == traced.start_time
+        )));
+    }
+}
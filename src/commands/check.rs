@@ -0,0 +1,46 @@
+use crate::budget::{evaluate, Budget, Outcome};
+use crate::reporters::generate_junit;
+use crate::sample_log::read_telemetry;
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::Path;
+
+/// 記録データを予算と照らし合わせ、結果のテーブルを出力する
+///
+/// 守れなかった予算があればエラーにする (終了ステータスが0以外になる)。
+/// `junit` を指定すると結果を JUnit XML でも書き出す。
+pub fn run(input: &Path, budgets: &[Budget], junit: Option<&Path>) -> Result<()> {
+    if budgets.is_empty() {
+        bail!("No budgets to check (set [check] budgets in the config, TELEMETRY_BUDGETS or --budget)");
+    }
+    let data = read_telemetry(input)?;
+    let outcomes = evaluate(budgets, &data);
+
+    print!("{}", outcome_table(&outcomes));
+    if let Some(path) = junit {
+        fs::write(path, generate_junit(&outcomes)).with_context(|| format!("Failed to write {}", path.display()))?;
+        eprintln!("✅ Saved to {}", path.display());
+    }
+
+    let failures = outcomes.iter().filter(|outcome| !outcome.passed()).count();
+    if failures > 0 {
+        bail!("{} of {} budgets failed", failures, outcomes.len());
+    }
+    eprintln!("✅ All {} budgets passed", outcomes.len());
+    Ok(())
+}
+
+fn outcome_table(outcomes: &[Outcome]) -> String {
+    let mut table = String::new();
+    table.push_str("| Budget | Actual | Result |\n");
+    table.push_str("|--------|-------:|:------:|\n");
+    for outcome in outcomes {
+        table.push_str(&format!(
+            "| {} | {} | {} |\n",
+            outcome.budget,
+            outcome.actual_text(),
+            if outcome.passed() { "✅" } else { "❌" }
+        ));
+    }
+    table
+}
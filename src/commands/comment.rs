@@ -0,0 +1,61 @@
+use crate::cli::CommentArgs;
+use crate::config::Config;
+use crate::output::ensure_dir;
+use crate::reporters::comment::comment_marker;
+use crate::reporters::{generate_comment, Summary};
+use crate::sample_log::read_telemetry;
+use anyhow::{Context, Result};
+use std::env;
+
+use super::report::charts;
+use super::write_output;
+
+/// PRコメントの本文をファイルに書き出す
+///
+/// 投稿はワークフロー側 (`gh`) で行うので、ネットワークには接続しない。
+/// 出力先を省略すると `<dir>/<prefix>-comment.md` に書く。
+pub fn run(args: &CommentArgs, config: &Config) -> Result<()> {
+    let input = args.input.clone().unwrap_or_else(|| config.output.default_input());
+    let data = read_telemetry(&input)?;
+    let summary = Summary::from_data(&data).with_context(|| format!("No samples in {}", input.display()))?;
+    let baseline = match &args.baseline {
+        Some(path) => Some(
+            Summary::from_data(&read_telemetry(path)?).with_context(|| format!("No samples in {}", path.display()))?,
+        ),
+        None => None,
+    };
+
+    // data URI はコメントには大きすぎるので、公開先のURLがあるときだけリンクする
+    let charts = match &args.chart_url {
+        Some(url) => charts(&data, &super::steps(&data, &config.runner), config, Some(url)),
+        None => Vec::new(),
+    };
+
+    let comment = generate_comment(
+        &comment_marker(args.id.as_deref()),
+        &summary,
+        baseline.as_ref(),
+        &charts,
+        run_url().as_deref(),
+    );
+
+    let output = match &args.output {
+        Some(path) => path.clone(),
+        None => {
+            ensure_dir(&config.output.dir())?;
+            config.output.file_path("comment.md")
+        }
+    };
+    write_output(Some(&output), &comment)
+}
+
+/// GitHub Actions の実行中なら、このワークフロー実行のURL
+fn run_url() -> Option<String> {
+    let var = |key| env::var(key).ok().filter(|value| !value.is_empty());
+    Some(format!(
+        "{}/{}/actions/runs/{}",
+        var("GITHUB_SERVER_URL")?,
+        var("GITHUB_REPOSITORY")?,
+        var("GITHUB_RUN_ID")?
+    ))
+}
@@ -0,0 +1,104 @@
+use crate::charts::generate_overlay_chart;
+use crate::cli::CompareArgs;
+use crate::config::OutputConfig;
+use crate::data::TelemetryData;
+use crate::output::ensure_dir;
+use crate::reporters::{comparison_table, highlights, Summary};
+use crate::sample_log::read_telemetry;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+/// `--trim-idle` で記録の先頭から除くサンプルのCPU使用率 (%)
+const IDLE_CPU_PERCENT: f64 = 5.0;
+
+/// 2つの記録を経過時間でそろえて比較し、差分を出力する
+///
+/// 統計値の差分は Markdown で標準出力に、両方を重ねたチャートは出力先ディレクトリに書き出す。
+pub fn run(args: &CompareArgs, output: &OutputConfig) -> Result<()> {
+    let offset_ms = args.offset.unsigned_abs() * 1000;
+    let (baseline_skip, current_skip) = if args.offset < 0 { (offset_ms, 0) } else { (0, offset_ms) };
+    let baseline = align(read_telemetry(&args.baseline)?, baseline_skip, args.trim_idle);
+    let current = align(read_telemetry(&args.current)?, current_skip, args.trim_idle);
+
+    let baseline_summary = Summary::from_data(&baseline)
+        .with_context(|| format!("No samples in {}", args.baseline.display()))?;
+    let current_summary = Summary::from_data(&current)
+        .with_context(|| format!("No samples in {}", args.current.display()))?;
+
+    print!("{}\n{}", highlights(&baseline_summary, &current_summary), comparison_table(&baseline_summary, &current_summary));
+
+    ensure_dir(&output.dir())?;
+    write_overlay_chart(&output.file_path("comparison.svg"), &baseline, &current);
+    Ok(())
+}
+
+/// 比較の開始 (経過時間0) をそろえた記録 (CPUとメモリのみ)
+///
+/// 先頭の `skip_ms` を除き、`trim_idle` なら最初にCPUを使い始めたサンプルより前も除く。
+fn align(data: TelemetryData, skip_ms: u64, trim_idle: bool) -> TelemetryData {
+    let first = data.cpu.first().map(|s| s.time).or_else(|| data.memory.first().map(|s| s.time));
+    let Some(mut start) = first else {
+        return data;
+    };
+    if trim_idle {
+        if let Some(busy) = data.cpu.iter().find(|s| s.total_load >= IDLE_CPU_PERCENT) {
+            start = busy.time;
+        }
+    }
+    start += skip_ms;
+
+    TelemetryData {
+        cpu: data.cpu.into_iter().filter(|s| s.time >= start).collect(),
+        memory: data.memory.into_iter().filter(|s| s.time >= start).collect(),
+        ..Default::default()
+    }
+}
+
+fn write_overlay_chart(path: &Path, baseline: &TelemetryData, current: &TelemetryData) {
+    match generate_overlay_chart(baseline, current) {
+        Ok(svg) if svg.is_empty() => {}
+        Ok(svg) => {
+            if let Err(e) = fs::write(path, &svg) {
+                eprintln!("Failed to write comparison chart: {}", e);
+            } else {
+                eprintln!("✅ Comparison chart saved to {}", path.display());
+            }
+        }
+        Err(e) => eprintln!("Failed to generate comparison chart: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::{CpuStats, MemoryStats};
+
+    #[test]
+    fn test_align() {
+        let cpu = |time: u64, total_load: f64| CpuStats {
+            time,
+            total_load,
+            user_load: total_load,
+            system_load: 0.0,
+            iowait_load: 0.0,
+            irq_load: 0.0,
+            softirq_load: 0.0,
+            steal_load: 0.0,
+            per_core: vec![],
+        };
+        let data = TelemetryData {
+            cpu: vec![cpu(1000, 1.0), cpu(3000, 2.0), cpu(5000, 80.0), cpu(7000, 60.0), cpu(9000, 10.0)],
+            memory: (0..5).map(|i| MemoryStats { time: 1000 + 2000 * i, ..Default::default() }).collect(),
+            ..Default::default()
+        };
+        let times = |data: &TelemetryData| data.cpu.iter().map(|s| s.time).collect::<Vec<_>>();
+
+        assert_eq!(times(&align(data.clone(), 0, false)), vec![1000, 3000, 5000, 7000, 9000]);
+        assert_eq!(times(&align(data.clone(), 2000, false)), vec![3000, 5000, 7000, 9000]);
+        assert_eq!(times(&align(data.clone(), 0, true)), vec![5000, 7000, 9000]);
+        let aligned = align(data, 2000, true);
+        assert_eq!(times(&aligned), vec![7000, 9000]);
+        assert_eq!(aligned.memory.len(), 2);
+    }
+}
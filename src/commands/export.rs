@@ -0,0 +1,18 @@
+use crate::cli::ExportFormat;
+use crate::reporters::{generate_csv, generate_openmetrics};
+use crate::sample_log::read_telemetry;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+use super::write_output;
+
+/// 記録データを指定の形式に変換して出力
+pub fn run(input: &Path, format: ExportFormat, output: Option<&Path>) -> Result<()> {
+    let data = read_telemetry(input)?;
+    let content = match format {
+        ExportFormat::Json => serde_json::to_string_pretty(&data).context("Failed to serialize JSON")? + "\n",
+        ExportFormat::Csv => generate_csv(&data),
+        ExportFormat::Openmetrics => generate_openmetrics(&data),
+    };
+    write_output(output, &content)
+}
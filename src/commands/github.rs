@@ -0,0 +1,25 @@
+use crate::config::Config;
+use crate::reporters::{github, Summary};
+use crate::sample_log::read_telemetry;
+use anyhow::Result;
+use std::path::Path;
+
+use super::report::build_report;
+
+/// GitHub Actions にレポート・統計値・閾値のアノテーションを書き出す
+///
+/// レポートはジョブサマリーに、統計値はステップの出力に書き、閾値を超えた項目は
+/// `::warning::` / `::error::` として標準出力に出す。
+pub fn run(input: &Path, chart_url: Option<&str>, config: &Config) -> Result<()> {
+    let data = read_telemetry(input)?;
+
+    github::append_step_summary(&build_report(&data, config, chart_url)?)?;
+    eprintln!("✅ Report appended to the step summary");
+
+    let Some(summary) = Summary::from_data(&data) else {
+        return Ok(());
+    };
+    github::write_outputs(&summary)?;
+    print!("{}", github::annotations(&summary.exceeded(&config.thresholds)));
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use crate::config::Config;
+use crate::output::ensure_dir;
+use crate::reporters::generate_html;
+use crate::sample_log::read_telemetry;
+use anyhow::Result;
+use std::path::Path;
+
+use super::write_output;
+
+/// 1ファイルで完結する HTML レポートを書き出す
+///
+/// 出力先を省略すると `<dir>/<prefix>-report.html` に書く。
+pub fn run(input: &Path, output: Option<&Path>, config: &Config) -> Result<()> {
+    let data = read_telemetry(input)?;
+    let html = generate_html(&data, &super::steps(&data, &config.runner));
+
+    let output = match output {
+        Some(path) => path.to_path_buf(),
+        None => {
+            ensure_dir(&config.output.dir())?;
+            config.output.file_path("report.html")
+        }
+    };
+    write_output(Some(&output), &html)
+}
@@ -0,0 +1,8 @@
+use crate::config::OutputConfig;
+use crate::control::{send_mark, Mark};
+use anyhow::Result;
+
+/// 記録中のプロセスにステップの開始 (`name` がNoneなら終了) を伝える
+pub fn run(output: &OutputConfig, name: Option<String>) -> Result<()> {
+    send_mark(&output.socket_path(), &Mark::now(name))
+}
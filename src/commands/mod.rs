@@ -0,0 +1,49 @@
+pub mod check;
+pub mod comment;
+pub mod compare;
+pub mod export;
+pub mod github;
+pub mod html;
+pub mod mark;
+pub mod record;
+pub mod render;
+pub mod report;
+pub mod run;
+pub mod summary;
+
+use crate::config::RunnerConfig;
+use crate::data::{Step, TelemetryData};
+use crate::runner;
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// `output` が指定されていればファイルに、なければ標準出力に書き出す
+fn write_output(output: Option<&Path>, content: &str) -> Result<()> {
+    match output {
+        Some(path) => {
+            fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!("✅ Saved to {}", path.display());
+        }
+        None => io::stdout().write_all(content.as_bytes()).context("Failed to write to stdout")?,
+    }
+    Ok(())
+}
+
+/// チャートとレポートで区切るステップ
+///
+/// `telemetry mark` のマーカーがあればそれを、なければランナーの診断ログから読んだステップを使う。
+/// 記録期間と重ならないステップは除く。
+fn steps(data: &TelemetryData, config: &RunnerConfig) -> Vec<Step> {
+    let times = data.cpu.iter().map(|s| s.time).chain(data.memory.iter().map(|s| s.time));
+    let (Some(start), Some(end)) = (times.clone().min(), times.max()) else {
+        return Vec::new();
+    };
+    let steps = if data.steps.is_empty() {
+        runner::read_steps(config.diag_dir.as_deref())
+    } else {
+        data.steps.clone()
+    };
+    runner::overlapping(steps, start, end)
+}
@@ -0,0 +1,279 @@
+use crate::collectors::{
+    CgroupCollector, CpuCollector, DiskCollector, MemoryCollector, NetworkCollector,
+    PressureCollector, ProcessCollector, ProcessTracer,
+};
+use crate::config::Config;
+use crate::control::ControlSocket;
+use crate::exporter::Exporter;
+use crate::output::{ensure_dir, write_atomic, RunLock};
+use crate::redact::Redactor;
+use crate::sample_log::{read_telemetry, LogRecord, Sample, SampleLog};
+use crate::shutdown::{Shutdown, Signal};
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// 記録を行い、プロセスの終了ステータスを返す
+pub fn run(config: &Config) -> i32 {
+    let mut session = match Session::start(config) {
+        Ok(session) => session,
+        Err(e) => {
+            eprintln!("{:#}", e);
+            return 1;
+        }
+    };
+
+    eprintln!(
+        "Telemetry monitoring started (max {} iterations at {}s intervals)",
+        config.record.iterations, config.record.interval
+    );
+    session.sample(config, Some(config.record.iterations));
+
+    let signal = session.shutdown.signal();
+    if let Err(e) = session.finish(config) {
+        eprintln!("{:#}", e);
+        return 1;
+    }
+    signal.map(Signal::exit_code).unwrap_or(0)
+}
+
+/// 1回の記録 (ロック・サンプルログ・シグナル受信・制御ソケット・`/metrics` を保持する)
+pub(super) struct Session {
+    pub shutdown: Arc<Shutdown>,
+    log: Arc<Mutex<SampleLog>>,
+    /// `sample` で使うプロセストレース
+    ///
+    /// トレースは作成時より後に起動したプロセスだけを対象にするので、`run` が子プロセスを起動する前に作っておく。
+    tracer: Option<ProcessTracer>,
+    _control: Option<ControlSocket>,
+    exporter: Option<Exporter>,
+    _lock: RunLock,
+}
+
+impl Session {
+    /// シグナル受信を開始し、出力先をロックしてサンプルログを作成
+    pub fn start(config: &Config) -> Result<Self> {
+        // 他のスレッドを作る前にシグナルをブロックする
+        let shutdown = Shutdown::install().context("Failed to install signal handling")?;
+
+        // 同じ出力先に書き込む別の記録プロセスがいれば、ファイルを壊す前に止める
+        ensure_dir(&config.output.dir())?;
+        let lock = RunLock::acquire(&config.output.lock_path())?;
+
+        // 前回の記録のJSONが残っていると、この記録が強制終了されたときに読み込むコマンドが古いJSONを読んでしまう
+        let json_path = config.output.json_path();
+        match fs::remove_file(&json_path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e).with_context(|| format!("Failed to remove {}", json_path.display())),
+        }
+
+        // サンプルはメモリに溜めず、取得するたびにログへ追記する
+        let log = SampleLog::create(&config.output.sample_log_path()).context("Failed to open sample log")?;
+        let log = Arc::new(Mutex::new(log));
+        eprintln!("Recording to {}", config.output.sample_log_path().display());
+
+        // `telemetry mark` を受け付けられなくても記録は続ける
+        let control = match ControlSocket::bind(&config.output.socket_path(), log.clone()) {
+            Ok(control) => Some(control),
+            Err(e) => {
+                eprintln!("Step markers are disabled: {:#}", e);
+                None
+            }
+        };
+
+        // `/metrics` を返せなくても記録は続ける
+        let exporter = config.record.listen.and_then(|addr| match Exporter::bind(addr) {
+            Ok(exporter) => {
+                eprintln!("Serving metrics at http://{}/metrics", exporter.local_addr());
+                Some(exporter)
+            }
+            Err(e) => {
+                eprintln!("Metrics endpoint is disabled: {:#}", e);
+                None
+            }
+        });
+
+        let redactor = Redactor::new(config.record.mask_values.clone());
+        let tracer = match config.collectors.trace.then(|| ProcessTracer::new(redactor)).transpose() {
+            Ok(tracer) => tracer,
+            Err(e) => {
+                eprintln!("Process Trace Error: {}", e);
+                None
+            }
+        };
+
+        Ok(Self {
+            shutdown,
+            log,
+            tracer,
+            _control: control,
+            exporter,
+            _lock: lock,
+        })
+    }
+
+    /// 停止が要求されるか `max_iterations` 回に達するまでサンプリングし、取得したサンプル数を返す
+    pub fn sample(&mut self, config: &Config, max_iterations: Option<u64>) -> u64 {
+        let collectors = &config.collectors;
+        let mut cpu_collector = collectors.cpu.then(CpuCollector::new);
+        let memory_collector = collectors.memory.then(MemoryCollector::new);
+        let mut network_collector = collectors.network.then(NetworkCollector::new);
+        let mut disk_collector = collectors.disk.then(DiskCollector::new);
+        let mut pressure_collector = collectors.pressure.then(PressureCollector::new);
+        let mut cgroup_collector = if collectors.cgroup { CgroupCollector::detect() } else { None };
+
+        let redactor = Redactor::new(config.record.mask_values.clone());
+        let mut process_collector = collectors
+            .processes
+            .then(|| ProcessCollector::new(config.record.top_processes, redactor.clone()));
+
+        // プロセストレースはサンプリングより短い間隔で別スレッドからポーリングする
+        let tracer_handle = self.tracer.take().map(|mut tracer| {
+            let shutdown = self.shutdown.clone();
+            let log_clone = self.log.clone();
+            let trace_interval = Duration::from_millis(config.record.trace_interval_ms);
+            thread::spawn(move || loop {
+                match tracer.poll() {
+                    Ok(events) => {
+                        let mut log = log_clone.lock().unwrap();
+                        for event in events {
+                            if let Err(e) = log.append(&LogRecord::Trace(event)) {
+                                eprintln!("Sample Log Error: {:#}", e);
+                            }
+                        }
+                    }
+                    Err(e) => eprintln!("Process Trace Error: {}", e),
+                }
+                // 停止後にもう一度だけポーリングして終了イベントを拾う
+                if shutdown.is_stopped() {
+                    break;
+                }
+                shutdown.wait_timeout(trace_interval);
+            })
+        });
+
+        let mut count = 0;
+        loop {
+            let mut sample = Sample::default();
+            if let Some(collector) = cpu_collector.as_mut() {
+                match collector.collect() {
+                    Ok(stats) => sample.cpu = Some(stats),
+                    Err(e) => eprintln!("CPU Error: {}", e),
+                }
+            }
+            if let Some(collector) = memory_collector.as_ref() {
+                match collector.collect() {
+                    Ok(stats) => sample.memory = Some(stats),
+                    Err(e) => eprintln!("Memory Error: {}", e),
+                }
+            }
+            if let Some(collector) = network_collector.as_mut() {
+                match collector.collect() {
+                    Ok(stats) => sample.network = Some(stats),
+                    Err(e) => eprintln!("Network Error: {}", e),
+                }
+            }
+            if let Some(collector) = disk_collector.as_mut() {
+                match collector.collect() {
+                    Ok(stats) => sample.disk = Some(stats),
+                    Err(e) => eprintln!("Disk Error: {}", e),
+                }
+            }
+            if let Some(collector) = pressure_collector.as_mut() {
+                match collector.collect() {
+                    Ok(stats) => sample.pressure = Some(stats),
+                    Err(e) => eprintln!("Pressure Error: {}", e),
+                }
+            }
+            if let Some(collector) = cgroup_collector.as_mut() {
+                match collector.collect() {
+                    Ok(stats) => sample.cgroup = Some(stats),
+                    Err(e) => eprintln!("Cgroup Error: {}", e),
+                }
+            }
+            if let Some(collector) = process_collector.as_mut() {
+                match collector.collect() {
+                    Ok(stats) => sample.processes = Some(stats),
+                    Err(e) => eprintln!("Process Error: {}", e),
+                }
+            }
+            if let Some(exporter) = &self.exporter {
+                exporter.update(&sample);
+            }
+            self.append(&LogRecord::Sample(Box::new(sample)));
+            count += 1;
+            let _ = writeln!(io::stderr(), "Collected data point {}", count);
+            let _ = io::stderr().flush();
+
+            // シグナルを受けると待機を切り上げ、最後のサンプルを取ってから抜ける
+            if self.shutdown.is_stopped() || max_iterations.is_some_and(|max| count >= max) {
+                break;
+            }
+            self.shutdown.wait_timeout(Duration::from_secs(config.record.interval));
+        }
+
+        // トレーサースレッドを止めて最後のイベントを書き切る
+        self.shutdown.stop();
+        if let Some(handle) = tracer_handle {
+            let _ = handle.join();
+        }
+
+        eprintln!("Collected {} data points, saving data...", count);
+        let _ = io::stderr().flush();
+        count
+    }
+
+    /// レコードをサンプルログに追記 (失敗しても記録は続ける)
+    pub fn append(&self, record: &LogRecord) {
+        if let Err(e) = self.log.lock().unwrap().append(record) {
+            eprintln!("Sample Log Error: {:#}", e);
+        }
+    }
+
+    /// サンプルログを同期し、JSONを書き出す
+    pub fn finish(self, config: &Config) -> Result<()> {
+        self.log.lock().unwrap().sync()?;
+        save_json_data(config)
+    }
+}
+
+/// サンプルログを読み直し、従来形式のJSONとして保存
+fn save_json_data(config: &Config) -> Result<()> {
+    let path = config.output.json_path();
+    let data = read_telemetry(&config.output.sample_log_path()).context("Failed to read sample log")?;
+    let json = serde_json::to_string_pretty(&data).context("Failed to serialize JSON")?;
+    write_atomic(&path, json.as_bytes()).context("Failed to write JSON")?;
+    eprintln!("✅ Data saved to {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OutputConfig;
+
+    #[test]
+    fn test_start_removes_previous_json() {
+        let config = Config {
+            output: OutputConfig {
+                dir: Some(std::env::temp_dir()),
+                prefix: Some(format!("telemetry-record-test-{}", std::process::id())),
+            },
+            ..Default::default()
+        };
+        fs::write(config.output.json_path(), "{}").unwrap();
+
+        let session = Session::start(&config).expect("Failed to start session");
+
+        // 終了前に強制終了されても前回のJSONではなくこの記録のサンプルログを読む
+        assert!(!config.output.json_path().exists());
+        assert_eq!(config.output.default_input(), config.output.sample_log_path());
+        drop(session);
+        let _ = fs::remove_file(config.output.sample_log_path());
+        let _ = fs::remove_file(config.output.lock_path());
+    }
+}
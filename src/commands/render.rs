@@ -0,0 +1,155 @@
+use crate::charts::{
+    draw_step_markers, generate_combined_chart, generate_cpu_chart, generate_cpu_heatmap_chart,
+    generate_disk_chart, generate_memory_chart, generate_network_chart,
+};
+use crate::config::Config;
+use crate::data::{Step, TelemetryData};
+use crate::output::ensure_dir;
+use crate::sample_log::read_telemetry;
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+
+/// 記録データからSVGチャートを生成して出力先ディレクトリに書き出す
+///
+/// 記録中に強制終了された途中までのサンプルログも読める。
+/// 個々のチャートの生成に失敗しても残りのチャートは書き出す。
+pub fn run(input: &Path, config: &Config) -> Result<()> {
+    let data = read_telemetry(input)?;
+    ensure_dir(&config.output.dir())?;
+
+    let steps = super::steps(&data, &config.runner);
+    for chart in render_charts(&data, &steps) {
+        write_chart(&config.output.file_path(chart.file_name), chart);
+    }
+    Ok(())
+}
+
+/// 旧形式の `--generate-svg` が書き出していたチャート
+const LEGACY_CHARTS: [&str; 3] = ["cpu-usage.svg", "memory-usage.svg", "combined-usage.svg"];
+
+/// 旧形式の `--generate-svg <json>`
+///
+/// アップロードするパスを変えずに済むよう、従来どおり3つのチャートだけを
+/// カレントディレクトリに接頭辞なしのファイル名で書き出す。
+pub fn run_legacy(input: &Path, config: &Config) -> Result<()> {
+    let data = read_telemetry(input)?;
+    let steps = super::steps(&data, &config.runner);
+    write_legacy_charts(&data, &steps, Path::new("."));
+    Ok(())
+}
+
+fn write_legacy_charts(data: &TelemetryData, steps: &[Step], dir: &Path) {
+    for chart in render_charts(data, steps) {
+        if LEGACY_CHARTS.contains(&chart.file_name) {
+            write_chart(&dir.join(chart.file_name), chart);
+        }
+    }
+}
+
+/// 生成したSVGチャート
+pub(super) struct RenderedChart {
+    /// 出力ファイル名 (接頭辞を除く)
+    pub file_name: &'static str,
+    pub title: &'static str,
+    pub svg: Result<String>,
+}
+
+/// 記録されている系列のチャートを生成 (統合グラフが先頭)
+///
+/// ステップ (`telemetry mark` またはランナーの診断ログ) があれば、時系列のチャートに境界線を描く。
+pub(super) fn render_charts(data: &TelemetryData, steps: &[Step]) -> Vec<RenderedChart> {
+    let with_steps = |svg: Result<String>, times: Vec<u64>| svg.map(|svg| draw_step_markers(svg, &times, steps));
+    let cpu_times = || data.cpu.iter().map(|s| s.time).collect();
+    let mut charts = Vec::new();
+    let mut push = |file_name, title, svg| charts.push(RenderedChart { file_name, title, svg });
+
+    // 統合グラフSVG生成
+    if !data.cpu.is_empty() && !data.memory.is_empty() {
+        push(
+            "combined-usage.svg",
+            "CPU and Memory Usage",
+            with_steps(generate_combined_chart(&data.cpu, &data.memory), cpu_times()),
+        );
+    }
+
+    // CPU SVG生成
+    if !data.cpu.is_empty() {
+        push("cpu-usage.svg", "CPU Usage", with_steps(generate_cpu_chart(&data.cpu), cpu_times()));
+    }
+
+    // コア別CPUヒートマップSVG生成
+    if data.cpu.iter().any(|s| !s.per_core.is_empty()) {
+        push("cpu-heatmap.svg", "Per-core CPU Usage", generate_cpu_heatmap_chart(&data.cpu));
+    }
+
+    // Memory SVG生成
+    if !data.memory.is_empty() {
+        let times = data.memory.iter().map(|s| s.time).collect();
+        push("memory-usage.svg", "Memory Usage", with_steps(generate_memory_chart(&data.memory), times));
+    }
+
+    // Network SVG生成
+    if !data.network.is_empty() {
+        let times = data.network.iter().map(|s| s.time).collect();
+        push("network-usage.svg", "Network I/O", with_steps(generate_network_chart(&data.network), times));
+    }
+
+    // Disk SVG生成
+    if !data.disk.is_empty() {
+        let times = data.disk.iter().map(|s| s.time).collect();
+        push("disk-usage.svg", "Disk I/O", with_steps(generate_disk_chart(&data.disk), times));
+    }
+
+    charts
+}
+
+fn write_chart(path: &Path, chart: RenderedChart) {
+    match chart.svg {
+        Ok(svg_data) => {
+            if let Err(e) = fs::write(path, &svg_data) {
+                eprintln!("Failed to write {} chart: {}", chart.title, e);
+            } else {
+                eprintln!("✅ {} chart saved to {}", chart.title, path.display());
+            }
+        }
+        Err(e) => eprintln!("Failed to generate {} chart: {}", chart.title, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::{CpuStats, MemoryStats};
+
+    #[test]
+    fn test_write_legacy_charts() {
+        let dir = std::env::temp_dir().join(format!("telemetry-legacy-svg-{}", std::process::id()));
+        ensure_dir(&dir).unwrap();
+        let data = TelemetryData {
+            cpu: (1..=3)
+                .map(|i| CpuStats {
+                    time: i * 1000,
+                    total_load: 10.0,
+                    user_load: 10.0,
+                    system_load: 0.0,
+                    iowait_load: 0.0,
+                    irq_load: 0.0,
+                    softirq_load: 0.0,
+                    steal_load: 0.0,
+                    per_core: vec![10.0, 10.0],
+                })
+                .collect(),
+            memory: (1..=3).map(|i| MemoryStats { time: i * 1000, ..Default::default() }).collect(),
+            ..Default::default()
+        };
+
+        write_legacy_charts(&data, &[], &dir);
+
+        let mut files: Vec<String> =
+            fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().file_name().into_string().unwrap()).collect();
+        files.sort();
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(files, vec!["combined-usage.svg", "cpu-usage.svg", "memory-usage.svg"]);
+    }
+}
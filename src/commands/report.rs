@@ -0,0 +1,50 @@
+use crate::cli::ReportArgs;
+use crate::config::Config;
+use crate::data::{Step, TelemetryData};
+use crate::reporters::{generate_report, github, Chart};
+use crate::sample_log::read_telemetry;
+use anyhow::Result;
+use std::path::Path;
+
+use super::render::render_charts;
+use super::write_output;
+
+/// 記録データのMarkdownレポートをチャート付きで出力
+pub fn run(input: &Path, args: &ReportArgs, config: &Config) -> Result<()> {
+    let data = read_telemetry(input)?;
+    let report = build_report(&data, config, args.chart_url.as_deref())?;
+
+    if args.step_summary {
+        github::append_step_summary(&report)?;
+        eprintln!("✅ Report appended to the step summary");
+        if args.output.is_none() {
+            return Ok(());
+        }
+    }
+    write_output(args.output.as_deref(), &report)
+}
+
+/// チャートを埋め込んだMarkdownレポートを生成
+///
+/// チャートは `chart_url` があればそのURLの画像に、なければdata URIとして埋め込む。
+pub(super) fn build_report(data: &TelemetryData, config: &Config, chart_url: Option<&str>) -> Result<String> {
+    let steps = super::steps(data, &config.runner);
+    let charts = charts(data, &steps, config, chart_url);
+    generate_report(&data.cpu, &data.memory, &data.processes, &data.process_trace, &steps, &charts)
+}
+
+/// レポートに載せるチャート (`chart_url` があればそのURLの画像、なければdata URI)
+pub(super) fn charts(data: &TelemetryData, steps: &[Step], config: &Config, chart_url: Option<&str>) -> Vec<Chart> {
+    let mut charts = Vec::new();
+    for chart in render_charts(data, steps) {
+        match (chart.svg, chart_url) {
+            (Ok(_), Some(url)) => charts.push(Chart {
+                title: chart.title.to_string(),
+                url: format!("{}/{}-{}", url.trim_end_matches('/'), config.output.prefix(), chart.file_name),
+            }),
+            (Ok(svg), None) => charts.push(Chart::inline(chart.title, &svg)),
+            (Err(e), _) => eprintln!("Failed to generate {} chart: {}", chart.title, e),
+        }
+    }
+    charts
+}
@@ -0,0 +1,235 @@
+use crate::config::Config;
+use crate::data::CommandRun;
+use crate::redact::Redactor;
+use crate::sample_log::LogRecord;
+use crate::shutdown::unblock_in_child;
+use anyhow::{bail, Result};
+use std::io::{self, ErrorKind};
+use std::process::Command;
+use std::thread;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use super::record::Session;
+
+/// コマンドを実行しながら記録し、コマンドの終了ステータスを返す
+///
+/// 受信したシグナルはコマンドに転送し、記録はコマンドの終了まで続ける。
+pub fn run(config: &Config, command: &[String]) -> i32 {
+    let mut session = match Session::start(config) {
+        Ok(session) => session,
+        Err(e) => {
+            eprintln!("{:#}", e);
+            return 1;
+        }
+    };
+
+    let redactor = Redactor::new(config.record.mask_values.clone());
+    let start_time = current_timestamp_ms();
+    let started = Instant::now();
+    // 終了は wait4 で待つので Child は使わずPIDだけ受け取る
+    let spawned = unblock_in_child(Command::new(&command[0]).args(&command[1..])).spawn();
+    let pid = match spawned.map(|child| child.id()) {
+        Ok(pid) => pid,
+        Err(e) => {
+            eprintln!("Failed to run {}: {}", command[0], e);
+            // 起動できなかったことも記録に残す
+            let result = CommandRun {
+                command: redactor.redact_args(command).join(" "),
+                start_time,
+                end_time: start_time,
+                // シェルと同じ終了ステータス
+                exit_code: Some(match e.kind() {
+                    ErrorKind::NotFound => 127,
+                    ErrorKind::PermissionDenied => 126,
+                    _ => 1,
+                }),
+                error: Some(e.to_string()),
+                ..Default::default()
+            };
+            session.append(&LogRecord::Command(result.clone()));
+            if let Err(e) = session.finish(config) {
+                eprintln!("{:#}", e);
+            }
+            return result.exit_status();
+        }
+    };
+    session.shutdown.forward_to(pid);
+    eprintln!("Running {} (pid {})", command[0], pid);
+
+    // 子プロセスの終了を待つスレッド。終了したらサンプリングを止める
+    let shutdown = session.shutdown.clone();
+    let waiter = thread::spawn(move || {
+        // 回収した後のPIDにはシグナルを転送しない
+        let result = shutdown.wait_child(pid, || wait_with_rusage(pid));
+        let wall_time_ms = started.elapsed().as_millis() as u64;
+        shutdown.stop();
+        result.map(|(status, rusage)| (status, rusage, wall_time_ms))
+    });
+
+    session.sample(config, None);
+
+    let (status, rusage, wall_time_ms) = match waiter.join().expect("Wait thread panicked") {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("{:#}", e);
+            let _ = session.finish(config);
+            return 1;
+        }
+    };
+
+    let result = command_run(&redactor.redact_args(command).join(" "), start_time, wall_time_ms, status, &rusage);
+    session.append(&LogRecord::Command(result.clone()));
+    eprint!("{}", result.to_markdown());
+
+    if let Err(e) = session.finish(config) {
+        eprintln!("{:#}", e);
+    }
+    result.exit_status()
+}
+
+/// `wait4` で子プロセスの終了を待ち、終了ステータスとリソース使用量を返す
+fn wait_with_rusage(pid: u32) -> Result<(libc::c_int, libc::rusage)> {
+    let mut status: libc::c_int = 0;
+    // SAFETY: rusage はゼロ初期化した構造体で、wait4 が書き込む
+    let mut rusage: libc::rusage = unsafe { std::mem::zeroed() };
+    loop {
+        // SAFETY: 自分で起動した子プロセスを待つ。status と rusage は有効なポインタ
+        let ret = unsafe { libc::wait4(pid as libc::pid_t, &mut status, 0, &mut rusage) };
+        if ret == pid as libc::pid_t {
+            return Ok((status, rusage));
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() != ErrorKind::Interrupted {
+            bail!("Failed to wait for pid {}: {}", pid, err);
+        }
+    }
+}
+
+fn command_run(
+    command: &str,
+    start_time: u64,
+    wall_time_ms: u64,
+    status: libc::c_int,
+    rusage: &libc::rusage,
+) -> CommandRun {
+    let millis = |tv: libc::timeval| tv.tv_sec as u64 * 1000 + tv.tv_usec as u64 / 1000;
+
+    CommandRun {
+        command: command.to_string(),
+        start_time,
+        end_time: start_time + wall_time_ms,
+        exit_code: libc::WIFEXITED(status).then(|| libc::WEXITSTATUS(status)),
+        signal: libc::WIFSIGNALED(status).then(|| libc::WTERMSIG(status)),
+        error: None,
+        wall_time_ms,
+        user_time_ms: millis(rusage.ru_utime),
+        system_time_ms: millis(rusage.ru_stime),
+        // Linux では ru_maxrss は KB 単位
+        max_rss_kb: rusage.ru_maxrss as u64,
+        voluntary_context_switches: rusage.ru_nvcsw as u64,
+        involuntary_context_switches: rusage.ru_nivcsw as u64,
+    }
+}
+
+/// 現在のタイムスタンプ（ミリ秒）を取得
+fn current_timestamp_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OutputConfig;
+    use crate::sample_log::read_telemetry;
+
+    fn test_config(name: &str) -> Config {
+        let mut config = Config {
+            output: OutputConfig {
+                dir: Some(std::env::temp_dir()),
+                prefix: Some(format!("telemetry-run-test-{}-{}", std::process::id(), name)),
+            },
+            ..Default::default()
+        };
+        config.collectors.cpu = false;
+        config.collectors.memory = false;
+        config.collectors.network = false;
+        config.collectors.disk = false;
+        config.collectors.pressure = false;
+        config.collectors.cgroup = false;
+        config.collectors.processes = false;
+        config.record.trace_interval_ms = 50;
+        config
+    }
+
+    fn remove_outputs(config: &Config) {
+        let _ = std::fs::remove_file(config.output.sample_log_path());
+        let _ = std::fs::remove_file(config.output.json_path());
+        let _ = std::fs::remove_file(config.output.lock_path());
+    }
+
+    fn run_child(args: &[&str]) -> CommandRun {
+        let pid = Command::new(args[0]).args(&args[1..]).spawn().map(|child| child.id()).expect("Failed to spawn");
+        let (status, rusage) = wait_with_rusage(pid).expect("Failed to wait");
+        command_run(&args.join(" "), 1000, 10, status, &rusage)
+    }
+
+    #[test]
+    fn test_wait_with_rusage_exit_code() {
+        let result = run_child(&["sh", "-c", "exit 3"]);
+
+        assert_eq!(result.exit_code, Some(3));
+        assert_eq!(result.signal, None);
+        assert_eq!(result.exit_status(), 3);
+        assert_eq!(result.end_time, 1010);
+        assert!(result.max_rss_kb > 0);
+    }
+
+    #[test]
+    fn test_wait_with_rusage_signal() {
+        let result = run_child(&["sh", "-c", "kill -TERM $$"]);
+
+        assert_eq!(result.exit_code, None);
+        assert_eq!(result.signal, Some(libc::SIGTERM));
+        assert_eq!(result.exit_status(), 143);
+    }
+
+    #[test]
+    fn test_trace_includes_process_started_before_sampling() {
+        let config = test_config("trace");
+        let mut session = Session::start(&config).expect("Failed to start session");
+
+        // サンプリングを始める前に起動したプロセスもトレースされる
+        let mut child = Command::new("sleep").arg("5").spawn().expect("Failed to spawn sleep");
+        thread::sleep(std::time::Duration::from_millis(100));
+        session.shutdown.stop();
+        session.sample(&config, None);
+        child.kill().expect("Failed to kill sleep");
+        child.wait().expect("Failed to wait sleep");
+        session.finish(&config).expect("Failed to finish session");
+
+        let data = read_telemetry(&config.output.json_path()).expect("Failed to read data");
+        remove_outputs(&config);
+        assert!(data.process_trace.iter().any(|process| process.pid == child.id()));
+    }
+
+    #[test]
+    fn test_run_records_spawn_failure() {
+        let config = test_config("spawn-failure");
+
+        let status = run(&config, &["/nonexistent/telemetry-run-test".to_string(), "--token=abc".to_string()]);
+
+        let data = read_telemetry(&config.output.json_path()).expect("Failed to read data");
+        assert_eq!(status, 127);
+        let command = data.command.expect("Command was not recorded");
+        assert_eq!(command.command, "/nonexistent/telemetry-run-test --token=***");
+        assert_eq!(command.exit_code, Some(127));
+        assert!(command.error.is_some());
+        // ロックと制御ソケットは解放されている
+        assert!(!config.output.socket_path().exists());
+        assert!(crate::output::RunLock::acquire(&config.output.lock_path()).is_ok());
+        remove_outputs(&config);
+    }
+}
@@ -0,0 +1,23 @@
+use crate::config::Config;
+use crate::reporters::Summary;
+use crate::sample_log::read_telemetry;
+use anyhow::Result;
+use std::path::Path;
+
+/// 平均・ピークの統計値を出力し、閾値を超えた項目があれば警告する
+pub fn run(input: &Path, config: &Config) -> Result<()> {
+    let data = read_telemetry(input)?;
+    let Some(summary) = Summary::from_data(&data) else {
+        println!("⚠️ No data collected");
+        return Ok(());
+    };
+
+    print!("{}", summary.to_markdown());
+    if let Some(command) = &data.command {
+        print!("{}", command.to_markdown());
+    }
+    for exceeded in summary.exceeded(&config.thresholds) {
+        println!("{} {}", if exceeded.error { "❌" } else { "⚠️" }, exceeded.message);
+    }
+    Ok(())
+}
@@ -0,0 +1,383 @@
+use crate::budget::Budget;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+/// 設定ファイル (TOML)
+///
+/// 優先順位は デフォルト値 < 設定ファイル < 環境変数 < コマンドライン引数。
+/// 省略したキーはデフォルト値になる。
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub record: RecordConfig,
+    pub collectors: CollectorsConfig,
+    pub output: OutputConfig,
+    pub thresholds: Thresholds,
+    pub runner: RunnerConfig,
+    pub check: CheckConfig,
+}
+
+/// 記録の設定
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RecordConfig {
+    /// データ収集間隔 (秒)
+    pub interval: u64,
+    /// 最大収集回数
+    pub iterations: u64,
+    /// CPU・メモリ上位として記録するプロセス数
+    pub top_processes: usize,
+    /// プロセストレースのポーリング間隔 (ミリ秒)
+    pub trace_interval_ms: u64,
+    /// コマンドラインから追加でマスクする文字列
+    pub mask_values: Vec<String>,
+    /// 最新の値を `/metrics` で返すアドレス (例: `127.0.0.1:9464`)。省略時は待ち受けない
+    pub listen: Option<SocketAddr>,
+}
+
+impl Default for RecordConfig {
+    fn default() -> Self {
+        Self {
+            interval: 5,
+            iterations: 60,
+            top_processes: 5,
+            trace_interval_ms: 500,
+            mask_values: Vec::new(),
+            listen: None,
+        }
+    }
+}
+
+/// 有効にするコレクター
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CollectorsConfig {
+    pub cpu: bool,
+    pub memory: bool,
+    pub network: bool,
+    pub disk: bool,
+    pub pressure: bool,
+    pub cgroup: bool,
+    pub processes: bool,
+    pub trace: bool,
+}
+
+impl Default for CollectorsConfig {
+    fn default() -> Self {
+        Self {
+            cpu: true,
+            memory: true,
+            network: true,
+            disk: true,
+            pressure: true,
+            cgroup: true,
+            processes: true,
+            trace: true,
+        }
+    }
+}
+
+/// GitHub Actions 以外で実行したときのファイル名の接頭辞 (従来の `/tmp/telemetry_data.json` と同じ名前)
+const DEFAULT_PREFIX: &str = "telemetry_data";
+
+/// 出力先
+///
+/// ファイルはすべて `dir` の下に `prefix` を付けた名前で作る。
+/// 未設定の項目は `apply_env` で実行環境から決める。
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    /// 出力先ディレクトリ。省略時は `$RUNNER_TEMP`、なければシステムの一時ディレクトリ
+    pub dir: Option<PathBuf>,
+    /// ファイル名の接頭辞。省略時は `GITHUB_RUN_ID` / `GITHUB_JOB` から決める
+    pub prefix: Option<String>,
+}
+
+impl OutputConfig {
+    pub fn dir(&self) -> PathBuf {
+        self.dir.clone().unwrap_or_else(env::temp_dir)
+    }
+
+    pub fn prefix(&self) -> &str {
+        self.prefix.as_deref().unwrap_or(DEFAULT_PREFIX)
+    }
+
+    /// 記録中のサンプルを追記するログ (NDJSON)
+    pub fn sample_log_path(&self) -> PathBuf {
+        self.dir().join(format!("{}.ndjson", self.prefix()))
+    }
+
+    /// 記録終了時に書き出すJSON
+    pub fn json_path(&self) -> PathBuf {
+        self.dir().join(format!("{}.json", self.prefix()))
+    }
+
+    /// 同じ出力先への同時記録を防ぐロックファイル
+    pub fn lock_path(&self) -> PathBuf {
+        self.dir().join(format!("{}.lock", self.prefix()))
+    }
+
+    /// 記録中のプロセスが `telemetry mark` を待ち受けるソケット
+    pub fn socket_path(&self) -> PathBuf {
+        self.dir().join(format!("{}.sock", self.prefix()))
+    }
+
+    /// SVGチャートなどの出力ファイル
+    pub fn file_path(&self, name: &str) -> PathBuf {
+        self.dir().join(format!("{}-{}", self.prefix(), name))
+    }
+
+    /// 入力ファイルを省略したときに読む記録データ
+    ///
+    /// 記録が正常に終わっていればJSONを、強制終了などでJSONがなければサンプルログを読む
+    pub fn default_input(&self) -> PathBuf {
+        let json = self.json_path();
+        if json.exists() {
+            json
+        } else {
+            self.sample_log_path()
+        }
+    }
+
+    /// GitHub Actions の実行ごとに一意な接頭辞 (同じランナー上の別ジョブと衝突しないようにする)
+    fn github_prefix(var: impl Fn(&str) -> Option<String>) -> Option<String> {
+        let run_id = var("GITHUB_RUN_ID")?;
+        let mut prefix = format!("telemetry-{}", run_id);
+        if let Some(attempt) = var("GITHUB_RUN_ATTEMPT") {
+            prefix.push_str(&format!("-{}", attempt));
+        }
+        if let Some(job) = var("GITHUB_JOB") {
+            prefix.push_str(&format!("-{}", job));
+        }
+        // ジョブIDにはファイル名に使えない文字が入りうる
+        Some(
+            prefix
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+                .collect(),
+        )
+    }
+}
+
+/// 警告を出す閾値 (未設定なら判定しない)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Thresholds {
+    /// CPU使用率のピーク (%)。超えたら警告
+    pub cpu_percent: Option<f64>,
+    /// メモリ使用率のピーク (%)。超えたら警告
+    pub memory_percent: Option<f64>,
+    /// CPU使用率のピーク (%)。超えたらエラー
+    pub cpu_error_percent: Option<f64>,
+    /// メモリ使用率のピーク (%)。超えたらエラー
+    pub memory_error_percent: Option<f64>,
+}
+
+/// GitHub Actions ランナー
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RunnerConfig {
+    /// ステップの実行時間を読む診断ログ (`Worker_*.log`) のディレクトリ。
+    /// 省略時は祖先プロセスの `Runner.Worker` から探す
+    pub diag_dir: Option<PathBuf>,
+}
+
+/// `check` で判定するリソースの予算
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CheckConfig {
+    /// `peak_memory_mb <= 6000` のような式
+    pub budgets: Vec<Budget>,
+}
+
+impl Config {
+    /// 設定ファイルを読み込み、環境変数で上書きする
+    ///
+    /// `path` が None なら `TELEMETRY_CONFIG` のパスを使い、それもなければデフォルト値から始める。
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let path = path.map(Path::to_path_buf).or_else(|| env::var_os("TELEMETRY_CONFIG").map(PathBuf::from));
+        let mut config = match path {
+            Some(path) => {
+                let content = fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read config file {}", path.display()))?;
+                Self::parse(&content).with_context(|| format!("Invalid config file {}", path.display()))?
+            }
+            None => Self::default(),
+        };
+        config.apply_env(|key| env::var(key).ok())?;
+        Ok(config)
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// `TELEMETRY_*` 環境変数で上書き
+    fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<()> {
+        fn parse<T: std::str::FromStr>(key: &str, value: String) -> Result<T>
+        where
+            T::Err: std::error::Error + Send + Sync + 'static,
+        {
+            value.trim().parse().with_context(|| format!("Invalid value for {}: {:?}", key, value))
+        }
+
+        if let Some(value) = var("TELEMETRY_INTERVAL") {
+            self.record.interval = parse("TELEMETRY_INTERVAL", value)?;
+        }
+        if let Some(value) = var("TELEMETRY_ITERATIONS") {
+            self.record.iterations = parse("TELEMETRY_ITERATIONS", value)?;
+        }
+        if let Some(value) = var("TELEMETRY_TOP_PROCESSES") {
+            self.record.top_processes = parse("TELEMETRY_TOP_PROCESSES", value)?;
+        }
+        if let Some(value) = var("TELEMETRY_TRACE_INTERVAL_MS") {
+            self.record.trace_interval_ms = parse("TELEMETRY_TRACE_INTERVAL_MS", value)?;
+        }
+        // 改行区切り
+        if let Some(value) = var("TELEMETRY_MASK_VALUES") {
+            self.record
+                .mask_values
+                .extend(value.lines().map(|line| line.trim().to_string()).filter(|line| !line.is_empty()));
+        }
+        if let Some(value) = var("TELEMETRY_LISTEN") {
+            self.record.listen = Some(parse("TELEMETRY_LISTEN", value)?);
+        }
+        if let Some(value) = var("TELEMETRY_OUTPUT_DIR") {
+            self.output.dir = Some(PathBuf::from(value));
+        } else if self.output.dir.is_none() {
+            self.output.dir = var("RUNNER_TEMP").map(PathBuf::from);
+        }
+        if let Some(value) = var("TELEMETRY_OUTPUT_PREFIX") {
+            self.output.prefix = Some(value);
+        } else if self.output.prefix.is_none() {
+            self.output.prefix = OutputConfig::github_prefix(&var);
+        }
+        if let Some(value) = var("TELEMETRY_CPU_THRESHOLD") {
+            self.thresholds.cpu_percent = Some(parse("TELEMETRY_CPU_THRESHOLD", value)?);
+        }
+        if let Some(value) = var("TELEMETRY_MEMORY_THRESHOLD") {
+            self.thresholds.memory_percent = Some(parse("TELEMETRY_MEMORY_THRESHOLD", value)?);
+        }
+        if let Some(value) = var("TELEMETRY_CPU_ERROR_THRESHOLD") {
+            self.thresholds.cpu_error_percent = Some(parse("TELEMETRY_CPU_ERROR_THRESHOLD", value)?);
+        }
+        if let Some(value) = var("TELEMETRY_MEMORY_ERROR_THRESHOLD") {
+            self.thresholds.memory_error_percent = Some(parse("TELEMETRY_MEMORY_ERROR_THRESHOLD", value)?);
+        }
+        // 改行区切り
+        if let Some(value) = var("TELEMETRY_BUDGETS") {
+            for line in value.lines().map(str::trim).filter(|line| !line.is_empty()) {
+                self.check.budgets.push(line.parse().context("Invalid value for TELEMETRY_BUDGETS")?);
+            }
+        }
+        if let Some(value) = var("TELEMETRY_RUNNER_DIAG_DIR") {
+            self.runner.diag_dir = Some(PathBuf::from(value));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(
+            r#"
+            [record]
+            interval = 2
+            mask_values = ["hunter2"]
+            listen = "127.0.0.1:9464"
+
+            [collectors]
+            disk = false
+
+            [output]
+            dir = "/var/tmp/telemetry"
+
+            [thresholds]
+            cpu_percent = 90.0
+
+            [runner]
+            diag_dir = "/opt/actions-runner/_diag"
+
+            [check]
+            budgets = ["peak_memory_mb <= 6000", "job_duration <= 20m"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.record.interval, 2);
+        // 省略したキーはデフォルト値
+        assert_eq!(config.record.iterations, 60);
+        assert_eq!(config.record.listen, Some(SocketAddr::from(([127, 0, 0, 1], 9464))));
+        assert!(!config.collectors.disk);
+        assert!(config.collectors.cpu);
+        assert_eq!(config.output.json_path(), PathBuf::from("/var/tmp/telemetry/telemetry_data.json"));
+        assert_eq!(config.thresholds.cpu_percent, Some(90.0));
+        assert_eq!(config.thresholds.memory_percent, None);
+        assert_eq!(config.runner.diag_dir, Some(PathBuf::from("/opt/actions-runner/_diag")));
+        assert_eq!(config.check.budgets.len(), 2);
+        assert_eq!(config.check.budgets[1].limit, 1200.0);
+
+        assert!(Config::parse("[record]\ninterval_secs = 2\n").is_err());
+        assert!(Config::parse("[check]\nbudgets = [\"peak_memory <= 6000\"]\n").is_err());
+    }
+
+    #[test]
+    fn test_apply_env() {
+        let env: HashMap<&str, &str> = HashMap::from([
+            ("TELEMETRY_INTERVAL", "1"),
+            ("TELEMETRY_MASK_VALUES", "foo\n\n bar \n"),
+            ("TELEMETRY_MEMORY_THRESHOLD", "80"),
+            ("TELEMETRY_BUDGETS", "avg_cpu >= 40\nswap_used == 0\n"),
+        ]);
+        let mut config = Config::parse("[record]\ninterval = 10\nmask_values = [\"baz\"]\n").unwrap();
+        config.apply_env(|key| env.get(key).map(|v| v.to_string())).unwrap();
+
+        assert_eq!(config.record.interval, 1);
+        assert_eq!(config.record.mask_values, vec!["baz", "foo", "bar"]);
+        assert_eq!(config.thresholds.memory_percent, Some(80.0));
+        assert_eq!(config.check.budgets.len(), 2);
+
+        let mut config = Config::default();
+        assert!(config.apply_env(|key| (key == "TELEMETRY_INTERVAL").then(|| "soon".to_string())).is_err());
+        assert!(config.apply_env(|key| (key == "TELEMETRY_LISTEN").then(|| "localhost".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_output_paths_from_github_env() {
+        let env: HashMap<&str, &str> = HashMap::from([
+            ("RUNNER_TEMP", "/home/runner/work/_temp"),
+            ("GITHUB_RUN_ID", "1234"),
+            ("GITHUB_RUN_ATTEMPT", "2"),
+            ("GITHUB_JOB", "build/test"),
+        ]);
+        let mut config = Config::default();
+        config.apply_env(|key| env.get(key).map(|v| v.to_string())).unwrap();
+
+        assert_eq!(
+            config.output.sample_log_path(),
+            PathBuf::from("/home/runner/work/_temp/telemetry-1234-2-build_test.ndjson")
+        );
+        assert_eq!(
+            config.output.file_path("cpu-usage.svg"),
+            PathBuf::from("/home/runner/work/_temp/telemetry-1234-2-build_test-cpu-usage.svg")
+        );
+
+        // 設定ファイルや TELEMETRY_OUTPUT_* の指定が優先される
+        let mut config = Config::parse("[output]\nprefix = \"nightly\"\n").unwrap();
+        let env_with_dir = |key: &str| match key {
+            "TELEMETRY_OUTPUT_DIR" => Some("/data".to_string()),
+            _ => env.get(key).map(|v| v.to_string()),
+        };
+        config.apply_env(env_with_dir).unwrap();
+        assert_eq!(config.output.json_path(), PathBuf::from("/data/nightly.json"));
+    }
+}
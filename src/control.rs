@@ -0,0 +1,164 @@
+use crate::sample_log::{LogRecord, SampleLog};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 1回のやり取りの読み書きのタイムアウト
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// ステップの区切り (`telemetry mark`)
+///
+/// ソケットで送る1行と、サンプルログに書く1行は同じ形式
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Mark {
+    /// 時刻 (ミリ秒)
+    pub time: u64,
+    /// 開始するステップ名。Noneなら実行中のステップを終える
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+impl Mark {
+    /// 現在時刻のマーカー
+    pub fn now(name: Option<String>) -> Self {
+        Self { time: current_timestamp_ms(), name }
+    }
+}
+
+/// 記録中のプロセスが `telemetry mark` を待ち受ける Unix ドメインソケット
+///
+/// 受け取ったマーカーはサンプルログに追記してから応答するので、
+/// `telemetry mark` が成功した時点でマーカーはディスクに書かれている。
+/// 破棄するとソケットファイルを削除する。
+pub struct ControlSocket {
+    path: PathBuf,
+}
+
+impl ControlSocket {
+    /// `path` で待ち受けるスレッドを起動
+    ///
+    /// 前回の記録が残したソケットファイルは削除するので、出力先のロックを取ってから呼ぶこと。
+    pub fn bind(path: &Path, log: Arc<Mutex<SampleLog>>) -> Result<Self> {
+        let _ = fs::remove_file(path);
+        let listener =
+            UnixListener::bind(path).with_context(|| format!("Failed to listen on {}", path.display()))?;
+
+        // 記録終了時は accept で待ったままプロセスごと終了する
+        thread::Builder::new()
+            .name("control".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    let result = stream.map_err(anyhow::Error::from).and_then(|stream| handle(stream, &log));
+                    if let Err(e) = result {
+                        eprintln!("Control Socket Error: {:#}", e);
+                    }
+                }
+            })
+            .context("Failed to spawn control thread")?;
+
+        Ok(Self { path: path.to_path_buf() })
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// マーカーを1つ受け取ってログに追記し、`ok` かエラーメッセージを1行で返す
+fn handle(stream: UnixStream, log: &Mutex<SampleLog>) -> Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line).context("Failed to read mark")?;
+    let reply = match serde_json::from_str::<Mark>(&line) {
+        Ok(mark) => {
+            match &mark.name {
+                Some(name) => eprintln!("Step started: {}", name),
+                None => eprintln!("Step ended"),
+            }
+            match log.lock().unwrap().append(&LogRecord::Mark(mark)) {
+                Ok(()) => "ok".to_string(),
+                Err(e) => format!("{:#}", e),
+            }
+        }
+        Err(e) => format!("Invalid mark: {}", e),
+    };
+    (&stream).write_all(format!("{}\n", reply).as_bytes()).context("Failed to reply")?;
+    Ok(())
+}
+
+/// 記録中のプロセスにマーカーを送り、ログに書かれるまで待つ
+pub fn send_mark(path: &Path, mark: &Mark) -> Result<()> {
+    let stream = UnixStream::connect(path)
+        .with_context(|| format!("No recorder is listening on {}", path.display()))?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let mut line = serde_json::to_string(mark).context("Failed to serialize mark")?;
+    line.push('\n');
+    (&stream).write_all(line.as_bytes()).context("Failed to send mark")?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream).read_line(&mut reply).context("No reply from recorder")?;
+    match reply.trim() {
+        "ok" => Ok(()),
+        error => bail!("Recorder rejected the mark: {}", error),
+    }
+}
+
+/// 現在のタイムスタンプ（ミリ秒）を取得
+fn current_timestamp_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards")
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Step;
+    use crate::sample_log::read_telemetry;
+
+    #[test]
+    fn test_send_mark_to_recorder() {
+        let dir = std::env::temp_dir();
+        let socket_path = dir.join(format!("telemetry-control-{}.sock", std::process::id()));
+        let log_path = dir.join(format!("telemetry-control-{}.ndjson", std::process::id()));
+
+        let log = Arc::new(Mutex::new(SampleLog::create(&log_path).unwrap()));
+        let socket = ControlSocket::bind(&socket_path, log).unwrap();
+
+        let marks = [
+            Mark { time: 1000, name: Some("build".to_string()) },
+            Mark { time: 3000, name: Some("test".to_string()) },
+            Mark { time: 5000, name: None },
+        ];
+        for mark in &marks {
+            send_mark(&socket_path, mark).unwrap();
+        }
+        drop(socket);
+
+        let data = read_telemetry(&log_path).unwrap();
+        let _ = fs::remove_file(&log_path);
+
+        assert!(!socket_path.exists());
+        assert_eq!(
+            data.steps,
+            vec![
+                Step { name: "build".to_string(), start_time: 1000, end_time: Some(3000) },
+                Step { name: "test".to_string(), start_time: 3000, end_time: Some(5000) },
+            ]
+        );
+        assert!(send_mark(&socket_path, &marks[0]).is_err());
+    }
+}
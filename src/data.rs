@@ -0,0 +1,105 @@
+use crate::collectors::{
+    CgroupStats, CpuStats, DiskStats, MemoryStats, NetworkStats, PressureStats, ProcessStats,
+    TracedProcess,
+};
+use serde::{Deserialize, Serialize};
+
+/// 記録したテレメトリデータ (JSON出力の形式)
+///
+/// 古いバージョンで記録したJSONも読めるよう、後から追加した系列は省略可能にしている
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TelemetryData {
+    pub cpu: Vec<CpuStats>,
+    pub memory: Vec<MemoryStats>,
+    #[serde(default)]
+    pub network: Vec<NetworkStats>,
+    #[serde(default)]
+    pub disk: Vec<DiskStats>,
+    #[serde(default)]
+    pub pressure: Vec<PressureStats>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cgroup: Vec<CgroupStats>,
+    #[serde(default)]
+    pub processes: Vec<ProcessStats>,
+    #[serde(default)]
+    pub process_trace: Vec<TracedProcess>,
+    /// `telemetry run` で実行したコマンドの結果
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<CommandRun>,
+    /// `telemetry mark` で区切ったステップ
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<Step>,
+}
+
+/// `telemetry mark` で区切ったワークフローのステップ
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Step {
+    pub name: String,
+    /// 開始時刻 (ミリ秒)
+    pub start_time: u64,
+    /// 終了時刻 (ミリ秒)。記録終了時に実行中だったステップはNone
+    pub end_time: Option<u64>,
+}
+
+/// `telemetry run` で実行したコマンドの終了状態とリソース使用量 (getrusage)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CommandRun {
+    /// コマンドライン (機密情報マスク済み)
+    pub command: String,
+    /// 起動時刻 (ミリ秒)
+    pub start_time: u64,
+    /// 終了時刻 (ミリ秒)
+    pub end_time: u64,
+    /// 終了コード。シグナルで終了した場合はNone
+    pub exit_code: Option<i32>,
+    /// 終了させたシグナル番号
+    pub signal: Option<i32>,
+    /// コマンドを起動できなかった理由
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub wall_time_ms: u64,
+    pub user_time_ms: u64,
+    pub system_time_ms: u64,
+    /// 最大常駐セットサイズ (KB)。子孫プロセスのうち最大のもの
+    pub max_rss_kb: u64,
+    pub voluntary_context_switches: u64,
+    pub involuntary_context_switches: u64,
+}
+
+impl CommandRun {
+    /// シェルの慣例に合わせた終了ステータス (シグナルで終了した場合は 128 + シグナル番号)
+    pub fn exit_status(&self) -> i32 {
+        match (self.exit_code, self.signal) {
+            (Some(code), _) => code,
+            (None, Some(signal)) => 128 + signal,
+            (None, None) => 1,
+        }
+    }
+
+    /// Markdownのリストとして出力
+    pub fn to_markdown(&self) -> String {
+        let status = match (self.exit_code, self.signal) {
+            (Some(code), _) if self.error.is_some() => {
+                format!("exit code {} (failed to start: {})", code, self.error.as_deref().unwrap_or_default())
+            }
+            (Some(code), _) => format!("exit code {}", code),
+            (None, Some(signal)) => format!("killed by signal {}", signal),
+            (None, None) => "unknown".to_string(),
+        };
+        let mut text = String::new();
+        text.push_str(&format!("- **Command**: `{}`\n", self.command.replace('`', "'")));
+        text.push_str(&format!("- **Status**: {}\n", status));
+        text.push_str(&format!("- **Wall Time**: {:.2}s\n", self.wall_time_ms as f64 / 1000.0));
+        text.push_str(&format!(
+            "- **CPU Time**: {:.2}s user, {:.2}s sys\n",
+            self.user_time_ms as f64 / 1000.0,
+            self.system_time_ms as f64 / 1000.0
+        ));
+        text.push_str(&format!("- **Max RSS**: {} MB\n", self.max_rss_kb / 1024));
+        text.push_str(&format!(
+            "- **Context Switches**: {} voluntary, {} involuntary\n",
+            self.voluntary_context_switches, self.involuntary_context_switches
+        ));
+        text
+    }
+}
@@ -0,0 +1,155 @@
+use crate::reporters::current_openmetrics;
+use crate::sample_log::Sample;
+use anyhow::{Context, Result};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// 1回のやり取りの読み書きのタイムアウト
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// 記録中の最新のサンプルを `/metrics` で返す HTTP エンドポイント (Prometheus のスクレイプ用)
+///
+/// 最初のサンプルを取るまでは `# EOF` だけを返す。
+pub struct Exporter {
+    addr: SocketAddr,
+    latest: Arc<Mutex<Sample>>,
+}
+
+impl Exporter {
+    /// `addr` で待ち受けるスレッドを起動
+    pub fn bind(addr: SocketAddr) -> Result<Self> {
+        let listener = TcpListener::bind(addr).with_context(|| format!("Failed to listen on {}", addr))?;
+        // ポート0を指定した場合は割り当てられたポートを返す
+        let addr = listener.local_addr()?;
+        let latest = Arc::new(Mutex::new(Sample::default()));
+
+        // 記録終了時は accept で待ったままプロセスごと終了する
+        let latest_clone = latest.clone();
+        thread::Builder::new()
+            .name("exporter".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    // 何も送らないクライアントが他のスクレイプを待たせないよう、接続ごとに別スレッドで応答する
+                    let latest = latest_clone.clone();
+                    let spawned = stream.map_err(anyhow::Error::from).and_then(|stream| {
+                        thread::Builder::new()
+                            .name("exporter-conn".to_string())
+                            .spawn(move || {
+                                if let Err(e) = handle(stream, &latest) {
+                                    eprintln!("Metrics Endpoint Error: {:#}", e);
+                                }
+                            })
+                            .context("Failed to spawn connection thread")
+                    });
+                    if let Err(e) = spawned {
+                        eprintln!("Metrics Endpoint Error: {:#}", e);
+                    }
+                }
+            })
+            .context("Failed to spawn exporter thread")?;
+
+        Ok(Self { addr, latest })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// 返す値を最新のサンプルに差し替える
+    pub fn update(&self, sample: &Sample) {
+        *self.latest.lock().unwrap() = sample.clone();
+    }
+}
+
+/// リクエストを1つ読み、`GET /metrics` なら最新の値を返す
+fn handle(stream: TcpStream, latest: &Mutex<Sample>) -> Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).context("Failed to read request")?;
+    // ヘッダーは使わないが、読み切ってから応答する
+    let mut header = String::new();
+    loop {
+        header.clear();
+        if reader.read_line(&mut header).context("Failed to read request")? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts.next().unwrap_or_default().split('?').next().unwrap_or_default();
+    let (status, content_type, body) = match (method, path) {
+        ("GET" | "HEAD", "/metrics") => ("200 OK", CONTENT_TYPE, current_openmetrics(&latest.lock().unwrap())),
+        ("GET" | "HEAD", _) => ("404 Not Found", "text/plain; charset=utf-8", "Not Found\n".to_string()),
+        _ => ("405 Method Not Allowed", "text/plain; charset=utf-8", "Method Not Allowed\n".to_string()),
+    };
+
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    );
+    if method != "HEAD" {
+        response.push_str(&body);
+    }
+    (&stream).write_all(response.as_bytes()).context("Failed to send response")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::MemoryStats;
+    use std::io::Read;
+
+    fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\nAccept: */*\r\n\r\n", path).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_serve_latest_sample() {
+        let exporter = Exporter::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = exporter.local_addr();
+
+        let response = get(addr, "/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with("\r\n\r\n# EOF\n"));
+
+        exporter.update(&Sample {
+            memory: Some(MemoryStats { time: 1000, usage_percent: 42.5, ..Default::default() }),
+            ..Default::default()
+        });
+        let response = get(addr, "/metrics?name=x");
+        assert!(response.contains(&format!("\r\nContent-Type: {}\r\n", CONTENT_TYPE)));
+        assert!(response.contains("\nworkflow_telemetry_memory_usage_percent 42.5\n"));
+
+        assert!(get(addr, "/").starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+
+    #[test]
+    fn test_idle_client_does_not_block_scrape() {
+        let exporter = Exporter::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = exporter.local_addr();
+
+        // 接続したまま何も送らないクライアントがいても、すぐに応答する
+        let _idle = TcpStream::connect(addr).unwrap();
+        let started = std::time::Instant::now();
+        let response = get(addr, "/metrics");
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(started.elapsed() < TIMEOUT / 2);
+    }
+}
@@ -1,182 +1,95 @@
 mod collectors;
 mod reporters;
+mod budget;
 mod charts;
+mod cli;
+mod commands;
+mod config;
+mod control;
+mod data;
+mod exporter;
+mod output;
+mod redact;
+mod runner;
+mod sample_log;
+mod shutdown;
 
-use collectors::{CpuCollector, CpuStats, MemoryCollector, MemoryStats};
-use charts::{generate_cpu_chart, generate_memory_chart, generate_combined_chart};
-use std::env;
-use std::fs;
-use std::thread;
-use std::time::Duration;
-use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
-use std::sync::Mutex;
-use std::io::{self, Write};
+use clap::Parser;
+use cli::{Cli, Command, RecordArgs};
+use config::Config;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    
-    // SVG生成モード
-    if args.len() > 1 && args[1] == "--generate-svg" {
-        if args.len() < 3 {
-            eprintln!("Usage: {} --generate-svg <json_file>", args[0]);
-            std::process::exit(1);
-        }
-        generate_svg_from_json(&args[2]);
-        return;
-    }
-    
-    // 通常のモニタリングモード
-    run_monitoring();
-}
+    let cli = Cli::parse();
 
-fn generate_svg_from_json(json_path: &str) {
-    #[derive(serde::Deserialize)]
-    struct TelemetryData {
-        cpu: Vec<CpuStats>,
-        memory: Vec<MemoryStats>,
-    }
-    
-    match fs::read_to_string(json_path) {
-        Ok(json_content) => {
-            match serde_json::from_str::<TelemetryData>(&json_content) {
-                Ok(data) => {
-                    // CPU SVG生成
-                    if !data.cpu.is_empty() {
-                        match generate_cpu_chart(&data.cpu) {
-                            Ok(svg_data) => {
-                                if let Err(e) = fs::write("cpu-usage.svg", &svg_data) {
-                                    eprintln!("Failed to write CPU SVG: {}", e);
-                                } else {
-                                    eprintln!("✅ CPU chart saved to cpu-usage.svg");
-                                }
-                            }
-                            Err(e) => eprintln!("Failed to generate CPU chart: {}", e),
-                        }
-                    }
-                    
-                    // Memory SVG生成
-                    if !data.memory.is_empty() {
-                        match generate_memory_chart(&data.memory) {
-                            Ok(svg_data) => {
-                                if let Err(e) = fs::write("memory-usage.svg", &svg_data) {
-                                    eprintln!("Failed to write Memory SVG: {}", e);
-                                } else {
-                                    eprintln!("✅ Memory chart saved to memory-usage.svg");
-                                }
-                            }
-                            Err(e) => eprintln!("Failed to generate Memory chart: {}", e),
-                        }
-                    }
-
-                    // 統合グラフSVG生成
-                    if !data.cpu.is_empty() && !data.memory.is_empty() {
-                        match generate_combined_chart(&data.cpu, &data.memory) {
-                            Ok(svg_data) => {
-                                if let Err(e) = fs::write("combined-usage.svg", &svg_data) {
-                                    eprintln!("Failed to write combined SVG: {}", e);
-                                } else {
-                                    eprintln!("✅ Combined chart saved to combined-usage.svg");
-                                }
-                            }
-                            Err(e) => eprintln!("Failed to generate combined chart: {}", e),
-                        }
-                    }
-                }
-                Err(e) => eprintln!("Failed to parse JSON: {}", e),
-            }
+    let mut config = match Config::load(cli.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{:#}", e);
+            std::process::exit(2);
         }
-        Err(e) => eprintln!("Failed to read JSON file: {}", e),
+    };
+    if let Some(dir) = cli.output_dir {
+        config.output.dir = Some(dir);
     }
-}
-
-fn run_monitoring() {
-    let mut cpu_collector = CpuCollector::new();
-    let memory_collector = MemoryCollector::new();
-    let cpu_data = Arc::new(Mutex::new(Vec::<CpuStats>::new()));
-    let memory_data = Arc::new(Mutex::new(Vec::<MemoryStats>::new()));
-    let running = Arc::new(AtomicBool::new(true));
-
-    let interval_secs = env::var("TELEMETRY_INTERVAL")
-        .ok()
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(5);
-    
-    let max_iterations = env::var("TELEMETRY_ITERATIONS")
-        .ok()
-        .and_then(|s| s.parse().ok())
-        .unwrap_or(60);
-
-    eprintln!("Telemetry monitoring started (max {} iterations at {}s intervals)", max_iterations, interval_secs);
+    if let Some(prefix) = cli.prefix {
+        config.output.prefix = Some(prefix);
+    }
+    // 入力ファイルを省略した場合はこの実行の記録を読む
+    let input = |input: Option<std::path::PathBuf>| input.unwrap_or_else(|| config.output.default_input());
 
-    // SIGTERMハンドラー
-    let running_clone = running.clone();
-    let cpu_data_clone = cpu_data.clone();
-    let memory_data_clone = memory_data.clone();
-    
-    ctrlc::set_handler(move || {
-        let _ = writeln!(io::stderr(), "Received termination signal, saving data...");
-        let _ = io::stderr().flush();
-        running_clone.store(false, Ordering::SeqCst);
-        
-        // シグナル受信時にJSON保存
-        save_json_data(&cpu_data_clone.lock().unwrap(), &memory_data_clone.lock().unwrap());
-        std::process::exit(0);
-    }).expect("Error setting signal handler");
+    // 旧形式の `--generate-svg <json>` は render として扱う (出力先は従来どおり)
+    let legacy_svg = cli.command.is_none() && cli.generate_svg.is_some();
+    let command = match (cli.command, cli.generate_svg) {
+        (Some(command), _) => command,
+        (None, Some(input)) => Command::Render { input: Some(input) },
+        // サブコマンドなしで起動された場合は従来どおり記録する (index.js)
+        (None, None) => Command::Record(RecordArgs::default()),
+    };
 
-    let mut count = 0;
-    while running.load(Ordering::SeqCst) && count < max_iterations {
-        match cpu_collector.collect() {
-            Ok(stats) => {
-                cpu_data.lock().unwrap().push(stats);
-                let _ = writeln!(io::stderr(), "Collected CPU data point {}", count + 1);
-                let _ = io::stderr().flush();
+    let result = match command {
+        Command::Record(args) => {
+            if let Some(interval) = args.interval {
+                config.record.interval = interval;
+            }
+            if let Some(iterations) = args.iterations {
+                config.record.iterations = iterations;
             }
-            Err(e) => eprintln!("CPU Error: {}", e),
+            if args.listen.is_some() {
+                config.record.listen = args.listen;
+            }
+            std::process::exit(commands::record::run(&config));
         }
-        match memory_collector.collect() {
-            Ok(stats) => {
-                memory_data.lock().unwrap().push(stats);
+        Command::Run(args) => {
+            if let Some(interval) = args.interval {
+                config.record.interval = interval;
+            }
+            if args.listen.is_some() {
+                config.record.listen = args.listen;
             }
-            Err(e) => eprintln!("Memory Error: {}", e),
+            std::process::exit(commands::run::run(&config, &args.command));
         }
-        count += 1;
-        
-        if count < max_iterations && running.load(Ordering::SeqCst) {
-            thread::sleep(Duration::from_secs(interval_secs));
+        Command::Mark(args) => commands::mark::run(&config.output, args.name),
+        Command::Render { input: path } if legacy_svg => commands::render::run_legacy(&input(path), &config),
+        Command::Render { input: path } => commands::render::run(&input(path), &config),
+        Command::Report(args) => commands::report::run(&input(args.input.clone()), &args, &config),
+        Command::Html { input: path, output } => commands::html::run(&input(path), output.as_deref(), &config),
+        Command::Github { input: path, chart_url } => {
+            commands::github::run(&input(path), chart_url.as_deref(), &config)
         }
-    }
-
-    eprintln!("Collected {} data points, saving data...", count);
-    let _ = io::stderr().flush();
-
-    let cpu_vec = cpu_data.lock().unwrap().clone();
-    let mem_vec = memory_data.lock().unwrap().clone();
-    
-    save_json_data(&cpu_vec, &mem_vec);
-}
-
-fn save_json_data(cpu_data: &[CpuStats], memory_data: &[MemoryStats]) {
-    use serde::Serialize;
-    
-    #[derive(Serialize)]
-    struct TelemetryData {
-        cpu: Vec<CpuStats>,
-        memory: Vec<MemoryStats>,
-    }
-    
-    let data = TelemetryData {
-        cpu: cpu_data.to_vec(),
-        memory: memory_data.to_vec(),
-    };
-    
-    match serde_json::to_string_pretty(&data) {
-        Ok(json) => {
-            if let Err(e) = fs::write("/tmp/telemetry_data.json", &json) {
-                eprintln!("Failed to write JSON: {}", e);
-            } else {
-                eprintln!("✅ Data saved to /tmp/telemetry_data.json");
-            }
+        Command::Comment(args) => commands::comment::run(&args, &config),
+        Command::Summary { input: path } => commands::summary::run(&input(path), &config),
+        Command::Check(args) => {
+            config.check.budgets.extend(args.budgets);
+            commands::check::run(&input(args.input), &config.check.budgets, args.junit.as_deref())
+        }
+        Command::Compare(args) => commands::compare::run(&args, &config.output),
+        Command::Export { input: path, format, output } => {
+            commands::export::run(&input(path), format, output.as_deref())
         }
-        Err(e) => eprintln!("Failed to serialize JSON: {}", e),
+    };
+
+    if let Err(e) = result {
+        eprintln!("Error: {:#}", e);
+        std::process::exit(1);
     }
-}
\ No newline at end of file
+}
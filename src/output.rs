@@ -0,0 +1,96 @@
+use anyhow::{bail, Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, Write};
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+
+/// 同じ出力先に書き込む記録プロセスを1つに制限するロック
+///
+/// `flock` によるロックなので、プロセスが強制終了されてもカーネルが解放する。
+/// ロックファイル自体は削除しない (削除すると別プロセスが作り直したファイルと二重にロックできてしまう)。
+pub struct RunLock {
+    _file: File,
+}
+
+impl RunLock {
+    /// ロックを取得する。他のプロセスが保持していればエラー
+    pub fn acquire(path: &Path) -> Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .with_context(|| format!("Failed to open lock file {}", path.display()))?;
+
+        // SAFETY: 開いているファイルのディスクリプタを渡すだけ
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+            let mut holder = String::new();
+            let _ = file.read_to_string(&mut holder);
+            bail!(
+                "Another recorder (pid {}) is already writing to {}. Set a different output prefix or directory",
+                holder.trim(),
+                path.display()
+            );
+        }
+
+        file.set_len(0)?;
+        file.rewind()?;
+        write!(file, "{}", std::process::id())?;
+        Ok(Self { _file: file })
+    }
+}
+
+/// 一時ファイルに書いてから rename し、読み手が書き込み途中のファイルを見ないようにする
+///
+/// 一時ファイル名にはPIDを含めるので、同じ出力先に同時に書いても衝突しない。
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    let mut tmp_name = path.file_name().context("Output path has no file name")?.to_os_string();
+    tmp_name.push(format!(".{}.tmp", std::process::id()));
+    let tmp_path = path.with_file_name(tmp_name);
+
+    fs::write(&tmp_path, content).with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+    fs::rename(&tmp_path, path).with_context(|| {
+        let _ = fs::remove_file(&tmp_path);
+        format!("Failed to rename {} to {}", tmp_path.display(), path.display())
+    })
+}
+
+/// 出力先ディレクトリがなければ作成
+pub fn ensure_dir(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("telemetry-output-test-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_run_lock_is_exclusive() {
+        let path = temp_path("lock");
+        let lock = RunLock::acquire(&path).expect("Failed to acquire lock");
+
+        // 同じファイルを別に開いた場合も flock は競合する
+        let err = RunLock::acquire(&path).err().expect("Lock should be held");
+        assert!(err.to_string().contains(&format!("pid {}", std::process::id())));
+
+        drop(lock);
+        assert!(RunLock::acquire(&path).is_ok());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_write_atomic() {
+        let path = temp_path("data.json");
+        write_atomic(&path, b"{}").unwrap();
+        write_atomic(&path, b"{\"cpu\":[]}").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"cpu\":[]}");
+        let _ = fs::remove_file(&path);
+    }
+}
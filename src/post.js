@@ -1,15 +1,35 @@
 const core = require('@actions/core');
 const exec = require('@actions/exec');
 const fs = require('fs');
+const os = require('os');
 const path = require('path');
 
+// Same directory and prefix as the binary's other outputs (see OutputConfig in config.rs),
+// so jobs sharing a self-hosted runner don't overwrite each other's PID file
+function pidFilePath() {
+  const env = process.env;
+  const dir = env.TELEMETRY_OUTPUT_DIR ?? env.RUNNER_TEMP ?? os.tmpdir();
+  let prefix = env.TELEMETRY_OUTPUT_PREFIX;
+  if (prefix === undefined && env.GITHUB_RUN_ID !== undefined) {
+    prefix = `telemetry-${env.GITHUB_RUN_ID}`;
+    if (env.GITHUB_RUN_ATTEMPT !== undefined) {
+      prefix += `-${env.GITHUB_RUN_ATTEMPT}`;
+    }
+    if (env.GITHUB_JOB !== undefined) {
+      prefix += `-${env.GITHUB_JOB}`;
+    }
+    prefix = prefix.replace(/[^A-Za-z0-9_-]/gu, '_');
+  }
+  return path.join(dir, `${prefix ?? 'telemetry_data'}.pid`);
+}
+
 async function post() {
   try {
     core.info('Finishing telemetry monitoring...');
 
     // Get PID from state
     const pid = core.getState('telemetry-pid');
-    const pidFile = '/tmp/telemetry.pid';
+    const pidFile = pidFilePath();
 
     if (!pid && !fs.existsSync(pidFile)) {
       core.warning('Telemetry PID not found');
@@ -46,45 +66,27 @@ async function post() {
       // Process already stopped
     }
 
-    // Generate charts if data exists
-    const dataFile = '/tmp/telemetry_data.json';
-    if (!fs.existsSync(dataFile)) {
-      core.warning('No telemetry data found');
-      return;
-    }
-
-    core.info('Generating charts...');
+    // The binary resolves this run's output paths (RUNNER_TEMP, GITHUB_RUN_ID, GITHUB_JOB)
     const actionPath = process.env.GITHUB_ACTION_PATH || '.';
     const telemetryBinary = path.join(actionPath, 'telemetry');
 
-    await exec.exec(telemetryBinary, ['--generate-svg', dataFile]);
-
-    // Read data and calculate statistics
-    const data = JSON.parse(fs.readFileSync(dataFile, 'utf8'));
-
-    if (data.cpu && data.cpu.length > 0) {
-      const cpuLoads = data.cpu.map(s => s.total_load);
-      const avgCpu = (cpuLoads.reduce((a, b) => a + b, 0) / cpuLoads.length).toFixed(2);
-      const maxCpu = Math.max(...cpuLoads).toFixed(2);
-
-      const memPercents = data.memory.map(s => s.usage_percent);
-      const avgMem = (memPercents.reduce((a, b) => a + b, 0) / memPercents.length).toFixed(2);
-      const maxMem = Math.max(...memPercents).toFixed(2);
-
-      // Write summary
-      await core.summary
-        .addHeading('Workflow Telemetry Report')
-        .addList([
-          `**Data Points**: ${data.cpu.length}`,
-          `**CPU Average**: ${avgCpu}%`,
-          `**CPU Peak**: ${maxCpu}%`,
-          `**Memory Average**: ${avgMem}%`,
-          `**Memory Peak**: ${maxMem}%`
-        ])
-        .write();
+    core.info('Generating charts...');
+    const rendered = await exec.exec(telemetryBinary, ['render'], { ignoreReturnCode: true });
+    if (rendered !== 0) {
+      core.warning('No telemetry data found');
+      return;
+    }
 
-      core.info('Charts generated successfully');
+    // The binary writes the report to the step summary, usage to the step outputs,
+    // and threshold violations as annotations
+    const githubArgs = ['github'];
+    const chartUrl = core.getInput('chart-url');
+    if (chartUrl) {
+      githubArgs.push('--chart-url', chartUrl);
     }
+    await exec.exec(telemetryBinary, githubArgs);
+
+    core.info('Charts generated successfully');
   } catch (error) {
     core.warning(`Post action failed: ${error.message}`);
   }
@@ -0,0 +1,106 @@
+use crate::reporters::comparison::format_mb;
+use crate::reporters::{comparison_table, highlights, Chart, Summary};
+
+/// 既存のコメントを探して更新するための目印
+const MARKER_PREFIX: &str = "workflow-telemetry-comment";
+
+/// コメントに埋め込む目印 (`id` で同じPRへの複数のコメントを区別する)
+pub fn comment_marker(id: Option<&str>) -> String {
+    match id {
+        Some(id) => format!("<!-- {}:{} -->", MARKER_PREFIX, id),
+        None => format!("<!-- {} -->", MARKER_PREFIX),
+    }
+}
+
+/// PRコメントの本文を生成
+///
+/// 先頭行は `comment_marker` の目印で、ワークフローはこれで前回のコメントを見つけて更新する。
+/// 統計のテーブルと基準との主な差分は常に表示し、差分のテーブルとチャートへのリンクは折りたたむ。
+pub fn generate_comment(
+    marker: &str,
+    summary: &Summary,
+    baseline: Option<&Summary>,
+    charts: &[Chart],
+    run_url: Option<&str>,
+) -> String {
+    let mut comment = String::new();
+    comment.push_str(marker);
+    comment.push('\n');
+    comment.push_str("### 📊 Workflow Telemetry\n\n");
+
+    comment.push_str("| Duration | CPU Average | CPU Peak | Memory Average | Memory Peak |\n");
+    comment.push_str("|---------:|------------:|---------:|---------------:|------------:|\n");
+    comment.push_str(&format!(
+        "| {}s | {:.2}% | {:.2}% | {} ({:.2}%) | {} ({:.2}%) |\n\n",
+        summary.duration_secs,
+        summary.cpu_avg,
+        summary.cpu_peak,
+        format_mb(summary.memory_avg_mb),
+        summary.memory_avg_percent,
+        format_mb(summary.memory_peak_mb as f64),
+        summary.memory_peak_percent
+    ));
+
+    if let Some(baseline) = baseline {
+        comment.push_str("**Compared to baseline**\n\n");
+        comment.push_str(&highlights(baseline, summary));
+        comment.push_str("\n<details>\n<summary>All changes</summary>\n\n");
+        comment.push_str(&comparison_table(baseline, summary));
+        comment.push_str("\n</details>\n\n");
+    }
+
+    if !charts.is_empty() {
+        comment.push_str("<details>\n<summary>Charts</summary>\n\n");
+        for chart in charts {
+            comment.push_str(&format!("- [{}]({})\n", chart.title, chart.url));
+        }
+        comment.push_str("\n</details>\n\n");
+    }
+
+    if let Some(url) = run_url {
+        comment.push_str(&format!("Full report and artifacts: [workflow run]({})\n", url));
+    }
+    comment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_comment() {
+        let summary = Summary {
+            samples: 10,
+            duration_secs: 50,
+            cpu_avg: 20.0,
+            cpu_peak: 60.0,
+            memory_avg_percent: 30.0,
+            memory_avg_mb: 3000.0,
+            memory_peak_percent: 40.0,
+            memory_peak_mb: 5229,
+        };
+        let baseline = Summary { memory_peak_mb: 4000, ..summary.clone() };
+        let charts = vec![Chart {
+            title: "CPU Usage".to_string(),
+            url: "https://example.com/run-cpu-usage.svg".to_string(),
+        }];
+
+        let comment = generate_comment(
+            &comment_marker(Some("build")),
+            &summary,
+            Some(&baseline),
+            &charts,
+            Some("https://github.com/owner/repo/actions/runs/1"),
+        );
+
+        assert!(comment.starts_with("<!-- workflow-telemetry-comment:build -->\n"));
+        assert!(comment.contains("| 50s | 20.00% | 60.00% | 2.9 GB (30.00%) | 5.1 GB (40.00%) |\n"));
+        assert!(comment.contains("- Peak memory increased by 1.2 GB (3.9 GB → 5.1 GB)\n"));
+        assert!(comment.contains("- [CPU Usage](https://example.com/run-cpu-usage.svg)\n"));
+        assert!(comment.ends_with("[workflow run](https://github.com/owner/repo/actions/runs/1)\n"));
+
+        let comment = generate_comment(&comment_marker(None), &summary, None, &[], None);
+        assert!(comment.starts_with("<!-- workflow-telemetry-comment -->\n"));
+        assert!(!comment.contains("<details>"));
+    }
+}
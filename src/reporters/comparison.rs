@@ -0,0 +1,117 @@
+use crate::reporters::Summary;
+
+/// 主な変化を1行ずつ文章にしたリスト (「ピークメモリが 1.2 GB 増えた」など)
+pub fn highlights(baseline: &Summary, current: &Summary) -> String {
+    // (項目, 基準, 比較対象, 値の表示, 差の表示)
+    type Format = fn(f64) -> String;
+    let secs: Format = |secs| format!("{:.0}s", secs);
+    let percent: Format = |percent| format!("{:.2}%", percent);
+    let points: Format = |points| format!("{:.2} points", points);
+    let rows: [(&str, f64, f64, Format, Format); 4] = [
+        ("Duration", baseline.duration_secs as f64, current.duration_secs as f64, secs, secs),
+        ("Average CPU", baseline.cpu_avg, current.cpu_avg, percent, points),
+        ("Peak CPU", baseline.cpu_peak, current.cpu_peak, percent, points),
+        ("Peak memory", baseline.memory_peak_mb as f64, current.memory_peak_mb as f64, format_mb, format_mb),
+    ];
+
+    let mut text = String::new();
+    for (metric, baseline, current, value, delta) in rows {
+        let change = current - baseline;
+        let direction = if change > 0.0 {
+            "increased"
+        } else if change < 0.0 {
+            "decreased"
+        } else {
+            text.push_str(&format!("- {} unchanged ({})\n", metric, value(current)));
+            continue;
+        };
+        text.push_str(&format!(
+            "- {} {} by {} ({} → {})\n",
+            metric,
+            direction,
+            delta(change.abs()),
+            value(baseline),
+            value(current)
+        ));
+    }
+    text
+}
+
+/// MB を 1 GB 以上なら GB で表示
+pub fn format_mb(mb: f64) -> String {
+    if mb >= 1024.0 {
+        format!("{:.1} GB", mb / 1024.0)
+    } else {
+        format!("{:.0} MB", mb)
+    }
+}
+
+/// 統計値の差分の Markdown テーブル
+pub fn comparison_table(baseline: &Summary, current: &Summary) -> String {
+    let rows = [
+        ("Duration (s)", baseline.duration_secs as f64, current.duration_secs as f64),
+        ("CPU Average (%)", baseline.cpu_avg, current.cpu_avg),
+        ("CPU Peak (%)", baseline.cpu_peak, current.cpu_peak),
+        ("Memory Average (%)", baseline.memory_avg_percent, current.memory_avg_percent),
+        ("Memory Peak (%)", baseline.memory_peak_percent, current.memory_peak_percent),
+        ("Memory Peak (MB)", baseline.memory_peak_mb as f64, current.memory_peak_mb as f64),
+    ];
+
+    let mut table = String::new();
+    table.push_str("| Metric | Baseline | Current | Delta |\n");
+    table.push_str("|--------|---------:|--------:|------:|\n");
+    for (metric, baseline, current) in rows {
+        table.push_str(&format!(
+            "| {} | {:.2} | {:.2} | {:+.2} |\n",
+            metric,
+            baseline,
+            current,
+            current - baseline
+        ));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary() -> Summary {
+        Summary {
+            samples: 10,
+            duration_secs: 50,
+            cpu_avg: 20.0,
+            cpu_peak: 60.0,
+            memory_avg_percent: 30.0,
+            memory_peak_percent: 40.0,
+            memory_avg_mb: 3000.0,
+            memory_peak_mb: 4000,
+        }
+    }
+
+    #[test]
+    fn test_comparison_table() {
+        let baseline = summary();
+        let current = Summary { duration_secs: 45, cpu_peak: 75.5, ..baseline.clone() };
+
+        let table = comparison_table(&baseline, &current);
+
+        assert!(table.contains("| Duration (s) | 50.00 | 45.00 | -5.00 |\n"));
+        assert!(table.contains("| CPU Peak (%) | 60.00 | 75.50 | +15.50 |\n"));
+        assert!(table.contains("| Memory Peak (MB) | 4000.00 | 4000.00 | +0.00 |\n"));
+    }
+
+    #[test]
+    fn test_highlights() {
+        let baseline = summary();
+        let current = Summary { duration_secs: 45, cpu_avg: 25.5, memory_peak_mb: 5229, ..baseline.clone() };
+
+        assert_eq!(
+            highlights(&baseline, &current),
+            "- Duration decreased by 5s (50s → 45s)\n\
+             - Average CPU increased by 5.50 points (20.00% → 25.50%)\n\
+             - Peak CPU unchanged (60.00%)\n\
+             - Peak memory increased by 1.2 GB (3.9 GB → 5.1 GB)\n"
+        );
+    }
+}
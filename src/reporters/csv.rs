@@ -0,0 +1,319 @@
+use crate::data::TelemetryData;
+use chrono::{DateTime, SecondsFormat};
+
+/// 同じ行にまとめるサンプルの時刻の差の上限 (ミリ秒)
+///
+/// コレクターは1回のサンプリングで順に値を取るので、同じ回のサンプルでも時刻が少しずれる
+const ROW_TOLERANCE_MS: u64 = 500;
+
+/// 1つのコレクターの系列 (列名と、時刻ごとのセルの値)
+struct Group {
+    columns: Vec<String>,
+    samples: Vec<(u64, Vec<String>)>,
+}
+
+/// 全コレクターの系列を、1行1サンプリング・1列1メトリクスの CSV にする
+///
+/// 先頭の列はサンプルの時刻 (`time_ms` はエポックからのミリ秒、`timestamp` は RFC 3339)。
+/// 記録されていない系列の列は出力せず、その回に値のない系列のセルは空にする。
+pub fn generate_csv(data: &TelemetryData) -> String {
+    let groups = groups(data);
+
+    // 時刻順に並べ、同じ系列の値が既にある行や離れた時刻のサンプルは次の行にする
+    let mut samples: Vec<(u64, usize, &Vec<String>)> = groups
+        .iter()
+        .enumerate()
+        .flat_map(|(index, group)| group.samples.iter().map(move |(time, cells)| (*time, index, cells)))
+        .collect();
+    samples.sort_by_key(|(time, index, _)| (*time, *index));
+
+    let mut rows: Vec<(u64, Vec<Option<&Vec<String>>>)> = Vec::new();
+    for (time, index, cells) in samples {
+        match rows.last_mut() {
+            Some((row_time, row)) if row[index].is_none() && time - *row_time < ROW_TOLERANCE_MS => {
+                row[index] = Some(cells);
+            }
+            _ => {
+                let mut row = vec![None; groups.len()];
+                row[index] = Some(cells);
+                rows.push((time, row));
+            }
+        }
+    }
+
+    let mut csv = String::new();
+    let mut header = vec!["time_ms".to_string(), "timestamp".to_string()];
+    header.extend(groups.iter().flat_map(|group| group.columns.iter().cloned()));
+    push_record(&mut csv, &header);
+
+    for (time, row) in rows {
+        let timestamp = DateTime::from_timestamp_millis(time as i64)
+            .map(|time| time.to_rfc3339_opts(SecondsFormat::Millis, true))
+            .unwrap_or_default();
+        let mut record = vec![time.to_string(), timestamp];
+        for (group, cells) in groups.iter().zip(row) {
+            match cells {
+                Some(cells) => record.extend(cells.iter().cloned()),
+                None => record.extend(group.columns.iter().map(|_| String::new())),
+            }
+        }
+        push_record(&mut csv, &record);
+    }
+    csv
+}
+
+/// 記録されている系列ごとの列と値
+fn groups(data: &TelemetryData) -> Vec<Group> {
+    let mut groups = Vec::new();
+    let optional = |value: Option<String>| value.unwrap_or_default();
+
+    if !data.cpu.is_empty() {
+        let cores = data.cpu.iter().map(|s| s.per_core.len()).max().unwrap_or(0);
+        let mut columns = names(
+            "cpu",
+            &["total_percent", "user_percent", "system_percent", "iowait_percent", "irq_percent", "softirq_percent", "steal_percent"],
+        );
+        columns.extend((0..cores).map(|core| format!("cpu_core{}_percent", core)));
+        let samples = data
+            .cpu
+            .iter()
+            .map(|s| {
+                let mut cells: Vec<String> = [
+                    s.total_load,
+                    s.user_load,
+                    s.system_load,
+                    s.iowait_load,
+                    s.irq_load,
+                    s.softirq_load,
+                    s.steal_load,
+                ]
+                .iter()
+                .map(f64::to_string)
+                .collect();
+                cells.extend((0..cores).map(|core| optional(s.per_core.get(core).map(f64::to_string))));
+                (s.time, cells)
+            })
+            .collect();
+        groups.push(Group { columns, samples });
+    }
+
+    if !data.memory.is_empty() {
+        let columns = names(
+            "memory",
+            &[
+                "usage_percent", "used_mb", "total_mb", "limit_mb", "free_mb", "buffers_mb", "cached_mb",
+                "sreclaimable_mb", "shmem_mb", "dirty_mb", "writeback_mb", "swap_total_mb", "swap_used_mb",
+            ],
+        );
+        let samples = data
+            .memory
+            .iter()
+            .map(|s| {
+                let mut cells = vec![
+                    s.usage_percent.to_string(),
+                    s.used_mb.to_string(),
+                    s.total_mb.to_string(),
+                    optional(s.limit_mb.map(|mb| mb.to_string())),
+                ];
+                cells.extend(
+                    [
+                        s.free_mb,
+                        s.buffers_mb,
+                        s.cached_mb,
+                        s.sreclaimable_mb,
+                        s.shmem_mb,
+                        s.dirty_mb,
+                        s.writeback_mb,
+                        s.swap_total_mb,
+                        s.swap_used_mb,
+                    ]
+                    .iter()
+                    .map(u64::to_string),
+                );
+                (s.time, cells)
+            })
+            .collect();
+        groups.push(Group { columns, samples });
+    }
+
+    if !data.network.is_empty() {
+        let columns = names(
+            "network",
+            &[
+                "rx_bytes_per_sec", "tx_bytes_per_sec", "rx_packets_per_sec", "tx_packets_per_sec", "rx_errors",
+                "tx_errors", "rx_drops", "tx_drops",
+            ],
+        );
+        let samples = data
+            .network
+            .iter()
+            .map(|s| {
+                let mut cells: Vec<String> = [s.rx_bytes_per_sec, s.tx_bytes_per_sec, s.rx_packets_per_sec, s.tx_packets_per_sec]
+                    .iter()
+                    .map(f64::to_string)
+                    .collect();
+                cells.extend([s.rx_errors, s.tx_errors, s.rx_drops, s.tx_drops].iter().map(u64::to_string));
+                (s.time, cells)
+            })
+            .collect();
+        groups.push(Group { columns, samples });
+    }
+
+    if !data.disk.is_empty() {
+        // デバイスは最初に現れた順に列を並べる
+        let mut devices: Vec<&str> = Vec::new();
+        for device in data.disk.iter().flat_map(|s| &s.devices) {
+            if !devices.contains(&device.name.as_str()) {
+                devices.push(&device.name);
+            }
+        }
+        const FIELDS: [&str; 5] = ["read_bytes_per_sec", "write_bytes_per_sec", "read_iops", "write_iops", "busy_percent"];
+        let columns = devices
+            .iter()
+            .flat_map(|device| FIELDS.iter().map(move |field| format!("disk_{}_{}", device, field)))
+            .collect();
+        let samples = data
+            .disk
+            .iter()
+            .map(|s| {
+                let cells = devices
+                    .iter()
+                    .flat_map(|name| match s.devices.iter().find(|device| device.name == *name) {
+                        Some(d) => [d.read_bytes_per_sec, d.write_bytes_per_sec, d.read_iops, d.write_iops, d.busy_percent]
+                            .map(|value| value.to_string()),
+                        None => Default::default(),
+                    })
+                    .collect();
+                (s.time, cells)
+            })
+            .collect();
+        groups.push(Group { columns, samples });
+    }
+
+    if !data.pressure.is_empty() {
+        const FIELDS: [&str; 4] = ["some_avg10", "full_avg10", "some_stall_us", "full_stall_us"];
+        let columns = ["cpu", "memory", "io"]
+            .iter()
+            .flat_map(|resource| FIELDS.iter().map(move |field| format!("pressure_{}_{}", resource, field)))
+            .collect();
+        let samples = data
+            .pressure
+            .iter()
+            .map(|s| {
+                let cells = [&s.cpu, &s.memory, &s.io]
+                    .iter()
+                    .flat_map(|p| {
+                        [p.some_avg10.to_string(), p.full_avg10.to_string(), p.some_stall_us.to_string(), p.full_stall_us.to_string()]
+                    })
+                    .collect();
+                (s.time, cells)
+            })
+            .collect();
+        groups.push(Group { columns, samples });
+    }
+
+    if !data.cgroup.is_empty() {
+        let columns = names(
+            "cgroup",
+            &[
+                "memory_current_mb", "memory_max_mb", "oom", "oom_kill", "cpu_quota_cores", "nr_periods",
+                "nr_throttled", "throttled_usec",
+            ],
+        );
+        let samples = data
+            .cgroup
+            .iter()
+            .map(|s| {
+                let cells = vec![
+                    s.memory_current_mb.to_string(),
+                    optional(s.memory_max_mb.map(|mb| mb.to_string())),
+                    s.oom.to_string(),
+                    s.oom_kill.to_string(),
+                    optional(s.cpu_quota_cores.map(|cores| cores.to_string())),
+                    s.nr_periods.to_string(),
+                    s.nr_throttled.to_string(),
+                    s.throttled_usec.to_string(),
+                ];
+                (s.time, cells)
+            })
+            .collect();
+        groups.push(Group { columns, samples });
+    }
+
+    groups
+}
+
+fn names(prefix: &str, fields: &[&str]) -> Vec<String> {
+    fields.iter().map(|field| format!("{}_{}", prefix, field)).collect()
+}
+
+/// 1行を追加 (区切り文字・引用符・改行を含むフィールドは引用符で囲む)
+fn push_record(csv: &mut String, fields: &[String]) {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect();
+    csv.push_str(&fields.join(","));
+    csv.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::disk::DiskDeviceStats;
+    use crate::collectors::{DiskStats, MemoryStats, NetworkStats};
+
+    #[test]
+    fn test_generate_csv() {
+        let device = |name: &str, read: f64| DiskDeviceStats {
+            name: name.to_string(),
+            read_bytes_per_sec: read,
+            write_bytes_per_sec: 0.0,
+            read_iops: 0.0,
+            write_iops: 0.0,
+            busy_percent: 0.0,
+        };
+        let data = TelemetryData {
+            memory: vec![
+                MemoryStats { time: 1000, used_mb: 100, limit_mb: Some(512), ..Default::default() },
+                MemoryStats { time: 2001, used_mb: 200, ..Default::default() },
+            ],
+            // 最初の回はレートが計算できないので2回目から
+            network: vec![NetworkStats {
+                time: 2003,
+                rx_bytes_per_sec: 1.5,
+                tx_bytes_per_sec: 0.0,
+                rx_packets_per_sec: 0.0,
+                tx_packets_per_sec: 0.0,
+                rx_errors: 0,
+                tx_errors: 0,
+                rx_drops: 0,
+                tx_drops: 0,
+            }],
+            disk: vec![
+                DiskStats { time: 1002, devices: vec![device("sda", 10.0)] },
+                DiskStats { time: 2004, devices: vec![device("sda", 20.0), device("nvme,0", 30.0)] },
+            ],
+            ..Default::default()
+        };
+
+        let csv = generate_csv(&data);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("time_ms,timestamp,memory_usage_percent,memory_used_mb,memory_total_mb,memory_limit_mb,"));
+        assert!(lines[0].contains(",network_rx_bytes_per_sec,"));
+        assert!(lines[0].ends_with(",\"disk_nvme,0_busy_percent\""));
+        assert!(lines[1].starts_with("1000,1970-01-01T00:00:01.000Z,0,100,0,512,"));
+        // ネットワークとnvme,0の列は空
+        assert!(lines[1].ends_with(",0,,,,,,,,,10,0,0,0,0,,,,,"));
+        assert!(lines[2].starts_with("2001,1970-01-01T00:00:02.001Z,0,200,0,,"));
+        assert!(lines[2].ends_with(",1.5,0,0,0,0,0,0,0,20,0,0,0,0,30,0,0,0,0"));
+    }
+}
@@ -0,0 +1,108 @@
+use crate::reporters::{Exceeded, Summary};
+use anyhow::{Context, Result};
+use std::env;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+/// アノテーションのタイトル
+const ANNOTATION_TITLE: &str = "Workflow Telemetry";
+
+/// `$GITHUB_STEP_SUMMARY` にジョブサマリーを追記
+pub fn append_step_summary(content: &str) -> Result<()> {
+    append_env_file("GITHUB_STEP_SUMMARY", content)
+}
+
+/// 統計値を `$GITHUB_OUTPUT` に書き出す
+///
+/// 後続のステップから `steps.<id>.outputs.cpu-peak-percent` などで参照できる
+pub fn write_outputs(summary: &Summary) -> Result<()> {
+    append_env_file("GITHUB_OUTPUT", &outputs(summary))
+}
+
+/// `$GITHUB_OUTPUT` の形式 (`name=value` の行)
+fn outputs(summary: &Summary) -> String {
+    let values = [
+        ("data-points", summary.samples.to_string()),
+        ("duration-seconds", summary.duration_secs.to_string()),
+        ("cpu-average-percent", format!("{:.2}", summary.cpu_avg)),
+        ("cpu-peak-percent", format!("{:.2}", summary.cpu_peak)),
+        ("memory-average-percent", format!("{:.2}", summary.memory_avg_percent)),
+        ("memory-peak-percent", format!("{:.2}", summary.memory_peak_percent)),
+        ("memory-peak-mb", summary.memory_peak_mb.to_string()),
+    ];
+    values.iter().map(|(name, value)| format!("{}={}\n", name, value)).collect()
+}
+
+/// 閾値を超えた項目を `::warning::` / `::error::` のワークフローコマンドにする
+///
+/// 標準出力に書くとランナーがアノテーションとして表示する
+pub fn annotations(exceeded: &[Exceeded]) -> String {
+    exceeded
+        .iter()
+        .map(|exceeded| {
+            format!(
+                "::{} title={}::{}\n",
+                if exceeded.error { "error" } else { "warning" },
+                escape_property(ANNOTATION_TITLE),
+                escape_data(&exceeded.message)
+            )
+        })
+        .collect()
+}
+
+/// ワークフローコマンドのメッセージのエスケープ
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// ワークフローコマンドのプロパティ値のエスケープ
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// ランナーが用意したファイル (環境変数 `var` のパス) に追記
+fn append_env_file(var: &str, content: &str) -> Result<()> {
+    let path = env::var_os(var).with_context(|| format!("{} is not set", var))?;
+    let path = Path::new(&path);
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    file.write_all(content.as_bytes())
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outputs_and_annotations() {
+        let summary = Summary {
+            samples: 3,
+            duration_secs: 10,
+            cpu_avg: 30.0,
+            cpu_peak: 50.0,
+            memory_avg_percent: 50.0,
+            memory_peak_percent: 80.0,
+            memory_avg_mb: 500.0,
+            memory_peak_mb: 800,
+        };
+        let outputs = outputs(&summary);
+        assert!(outputs.starts_with("data-points=3\nduration-seconds=10\ncpu-average-percent=30.00\n"));
+        assert!(outputs.ends_with("memory-peak-percent=80.00\nmemory-peak-mb=800\n"));
+
+        let exceeded = vec![
+            Exceeded { error: false, message: "CPU peak 50.00% exceeds threshold 40.00%".to_string() },
+            Exceeded { error: true, message: "Memory peak\n80%".to_string() },
+        ];
+        assert_eq!(
+            annotations(&exceeded),
+            "::warning title=Workflow Telemetry::CPU peak 50.00%25 exceeds threshold 40.00%25\n\
+             ::error title=Workflow Telemetry::Memory peak%0A80%25\n"
+        );
+    }
+}
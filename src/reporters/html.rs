@@ -0,0 +1,244 @@
+use crate::collectors::TracedProcess;
+use crate::data::{Step, TelemetryData};
+use crate::reporters::markdown::process_peaks;
+use crate::reporters::Summary;
+use serde_json::{json, Value};
+
+/// レポートのテンプレート (CSS とチャートを描く JavaScript を含む)
+const TEMPLATE: &str = include_str!("report.html");
+
+const TITLE: &str = "Workflow Telemetry Report";
+
+/// 1ファイルで完結する HTML レポートを生成
+///
+/// 記録データは JSON として埋め込み、インラインの JavaScript がチャートを SVG で描く
+/// (ツールチップ、時間範囲のズーム、系列の表示切り替え)。外部のリソースは一切読み込まない。
+pub fn generate_html(data: &TelemetryData, steps: &[Step]) -> String {
+    let summary = match Summary::from_data(data) {
+        Some(summary) => summary_list(&summary),
+        None => "<p>⚠️ No data collected</p>\n".to_string(),
+    };
+
+    let mut tables = String::new();
+    if !data.processes.is_empty() {
+        tables.push_str(&top_processes_table(data));
+    }
+    if !data.process_trace.is_empty() {
+        tables.push_str(&process_trace_table(&data.process_trace));
+    }
+
+    TEMPLATE
+        .replace("{{TITLE}}", TITLE)
+        .replace("{{SUMMARY}}", &summary)
+        .replace("{{TABLES}}", &tables)
+        .replace("{{DATA}}", &script_json(&chart_data(data, steps)))
+}
+
+fn summary_list(summary: &Summary) -> String {
+    let items = [
+        ("Duration", format!("{}s", summary.duration_secs)),
+        ("Data Points", summary.samples.to_string()),
+        ("CPU Average", format!("{:.2}%", summary.cpu_avg)),
+        ("CPU Peak", format!("{:.2}%", summary.cpu_peak)),
+        ("Memory Average", format!("{:.0} MB ({:.2}%)", summary.memory_avg_mb, summary.memory_avg_percent)),
+        ("Memory Peak", format!("{} MB ({:.2}%)", summary.memory_peak_mb, summary.memory_peak_percent)),
+    ];
+
+    let mut list = String::from("<ul class=\"summary\">\n");
+    for (label, value) in items {
+        list.push_str(&format!("  <li>{}<b>{}</b></li>\n", label, value));
+    }
+    list.push_str("</ul>\n");
+    list
+}
+
+/// JavaScript に渡すデータ (記録の開始・終了時刻、ステップ、チャートごとの系列)
+fn chart_data(data: &TelemetryData, steps: &[Step]) -> Value {
+    const MB: f64 = 1024.0 * 1024.0;
+    let mut charts = Vec::new();
+
+    if !data.cpu.is_empty() {
+        charts.push(json!({
+            "title": "CPU Usage",
+            "unit": "%",
+            "max": 100,
+            "times": data.cpu.iter().map(|s| s.time).collect::<Vec<_>>(),
+            "series": [
+                series("Total", data.cpu.iter().map(|s| s.total_load)),
+                series("User", data.cpu.iter().map(|s| s.user_load)),
+                series("System", data.cpu.iter().map(|s| s.system_load)),
+                series("IOWait", data.cpu.iter().map(|s| s.iowait_load)),
+                series("Steal", data.cpu.iter().map(|s| s.steal_load)),
+            ],
+        }));
+    }
+    if !data.memory.is_empty() {
+        let gb = |mb: u64| mb as f64 / 1024.0;
+        charts.push(json!({
+            "title": "Memory Usage",
+            "unit": " GB",
+            "times": data.memory.iter().map(|s| s.time).collect::<Vec<_>>(),
+            "series": [
+                series("Used", data.memory.iter().map(|s| gb(s.used_mb))),
+                series("Cache", data.memory.iter().map(|s| gb(s.cached_mb + s.sreclaimable_mb))),
+                series("Swap", data.memory.iter().map(|s| gb(s.swap_used_mb))),
+            ],
+        }));
+    }
+    if !data.network.is_empty() {
+        charts.push(json!({
+            "title": "Network I/O",
+            "unit": " MB/s",
+            "times": data.network.iter().map(|s| s.time).collect::<Vec<_>>(),
+            "series": [
+                series("RX", data.network.iter().map(|s| s.rx_bytes_per_sec / MB)),
+                series("TX", data.network.iter().map(|s| s.tx_bytes_per_sec / MB)),
+            ],
+        }));
+    }
+    if !data.disk.is_empty() {
+        charts.push(json!({
+            "title": "Disk I/O",
+            "unit": " MB/s",
+            "times": data.disk.iter().map(|s| s.time).collect::<Vec<_>>(),
+            "series": [
+                series("Read", data.disk.iter().map(|s| s.total_read_bytes_per_sec() / MB)),
+                series("Write", data.disk.iter().map(|s| s.total_write_bytes_per_sec() / MB)),
+            ],
+        }));
+    }
+
+    let times = || charts.iter().filter_map(|chart| chart["times"].as_array()).flatten().filter_map(Value::as_u64);
+    json!({
+        "start": times().min(),
+        "end": times().max(),
+        "steps": steps
+            .iter()
+            .map(|step| json!({ "name": step.name, "start": step.start_time, "end": step.end_time }))
+            .collect::<Vec<_>>(),
+        "charts": charts,
+    })
+}
+
+/// 系列 (値はファイルサイズを抑えるため小数2桁に丸める)
+fn series(name: &str, values: impl Iterator<Item = f64>) -> Value {
+    json!({
+        "name": name,
+        "values": values.map(|v| (v * 100.0).round() / 100.0).collect::<Vec<_>>(),
+    })
+}
+
+/// `<script>` の中に置けるJSON (`</script>` で終わらないよう `<` をエスケープ)
+fn script_json(value: &Value) -> String {
+    value.to_string().replace('<', "\\u003c")
+}
+
+fn top_processes_table(data: &TelemetryData) -> String {
+    let mut table = String::new();
+    table.push_str("<h2>Top processes</h2>\n<table>\n<thead><tr>");
+    table.push_str("<th class=\"num\">PID</th><th class=\"num\">PPID</th><th>Process</th>");
+    table.push_str("<th class=\"num\">Peak CPU</th><th class=\"num\">Peak RSS</th><th>Command</th>");
+    table.push_str("</tr></thead>\n<tbody>\n");
+    for peak in process_peaks(&data.processes) {
+        table.push_str(&format!(
+            "<tr><td class=\"num\">{}</td><td class=\"num\">{}</td><td>{}</td>\
+             <td class=\"num\" data-sort=\"{}\">{:.1}%</td><td class=\"num\" data-sort=\"{}\">{} MB</td>\
+             <td><code>{}</code></td></tr>\n",
+            peak.pid,
+            peak.ppid,
+            escape_html(peak.comm),
+            peak.cpu_percent,
+            peak.cpu_percent,
+            peak.rss_mb,
+            peak.rss_mb,
+            escape_html(peak.cmdline),
+        ));
+    }
+    table.push_str("</tbody>\n</table>\n");
+    table
+}
+
+/// トレースしたプロセスを起動順に並べたテーブル (開始は記録の最初のプロセスからの経過時間)
+fn process_trace_table(process_trace: &[TracedProcess]) -> String {
+    let start = process_trace.iter().map(|p| p.start_time).min().unwrap_or(0);
+    let mut processes: Vec<&TracedProcess> = process_trace.iter().collect();
+    processes.sort_by_key(|p| (p.start_time, p.pid));
+
+    let mut table = String::new();
+    table.push_str("<h2>Process trace</h2>\n<table>\n<thead><tr>");
+    table.push_str("<th class=\"num\">PID</th><th class=\"num\">PPID</th><th>Process</th>");
+    table.push_str("<th class=\"num\">Start</th><th class=\"num\">Duration</th><th>Command</th>");
+    table.push_str("</tr></thead>\n<tbody>\n");
+    for process in processes {
+        let duration = match process.end_time {
+            Some(end) => {
+                let ms = end.saturating_sub(process.start_time);
+                format!("<td class=\"num\" data-sort=\"{}\">{:.1}s</td>", ms, ms as f64 / 1000.0)
+            }
+            None => "<td class=\"num\" data-sort=\"-1\">running</td>".to_string(),
+        };
+        let offset = process.start_time - start;
+        table.push_str(&format!(
+            "<tr><td class=\"num\">{}</td><td class=\"num\">{}</td><td>{}</td>\
+             <td class=\"num\" data-sort=\"{}\">+{:.1}s</td>{}<td><code>{}</code></td></tr>\n",
+            process.pid,
+            process.ppid,
+            escape_html(&process.comm),
+            offset,
+            offset as f64 / 1000.0,
+            duration,
+            escape_html(&process.cmdline),
+        ));
+    }
+    table.push_str("</tbody>\n</table>\n");
+    table
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::process::ProcessInfo;
+    use crate::collectors::{MemoryStats, ProcessStats};
+
+    #[test]
+    fn test_generate_html() {
+        let data = TelemetryData {
+            memory: (0..3)
+                .map(|i| MemoryStats { time: 1000 * (i + 1), used_mb: 1536, usage_percent: 15.0, ..Default::default() })
+                .collect(),
+            processes: vec![ProcessStats {
+                time: 1000,
+                top_cpu: vec![ProcessInfo {
+                    pid: 42,
+                    ppid: 1,
+                    start_time: 0,
+                    comm: "cargo".to_string(),
+                    cmdline: "cargo build --features <all>".to_string(),
+                    cpu_percent: 95.5,
+                    rss_mb: 300,
+                }],
+                top_memory: vec![],
+            }],
+            ..Default::default()
+        };
+        let steps = vec![Step { name: "</script>".to_string(), start_time: 1500, end_time: None }];
+
+        let html = generate_html(&data, &steps);
+
+        assert!(html.contains("<li>Memory Peak<b>1536 MB (15.00%)</b></li>"));
+        assert!(html.contains("<code>cargo build --features &lt;all&gt;</code>"));
+        assert!(html.contains(r#""end":3000,"start":1000,"#));
+        assert!(html.contains(r#"{"name":"Used","values":[1.5,1.5,1.5]}"#));
+        // ステップ名でスクリプトが終わらない
+        assert!(html.contains(r#"{"end":null,"name":"\u003c/script>","start":1500}"#));
+        // 外部のリソースを読み込まない
+        assert!(!html.contains("http://") && !html.contains("https://") && !html.contains(" src="));
+    }
+}
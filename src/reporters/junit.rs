@@ -0,0 +1,64 @@
+use crate::budget::Outcome;
+
+/// 予算の判定結果を JUnit XML にする
+///
+/// 予算1つを1つのテストケースとし、守れなかった予算を failure にする。
+pub fn generate_junit(outcomes: &[Outcome]) -> String {
+    let failures = outcomes.iter().filter(|outcome| !outcome.passed()).count();
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites name=\"telemetry\" tests=\"{}\" failures=\"{}\">\n",
+        outcomes.len(),
+        failures
+    ));
+    xml.push_str(&format!(
+        "  <testsuite name=\"budgets\" tests=\"{}\" failures=\"{}\">\n",
+        outcomes.len(),
+        failures
+    ));
+    for outcome in outcomes {
+        let name = escape_attribute(&outcome.budget.to_string());
+        if outcome.passed() {
+            xml.push_str(&format!("    <testcase classname=\"telemetry.budgets\" name=\"{}\"/>\n", name));
+            continue;
+        }
+        let message = format!("{} was {}, expected {}", outcome.budget.metric.name(), outcome.actual_text(), outcome.budget);
+        xml.push_str(&format!("    <testcase classname=\"telemetry.budgets\" name=\"{}\">\n", name));
+        xml.push_str(&format!("      <failure message=\"{}\"/>\n", escape_attribute(&message)));
+        xml.push_str("    </testcase>\n");
+    }
+    xml.push_str("  </testsuite>\n");
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+fn escape_attribute(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_junit() {
+        let outcomes = vec![
+            Outcome { budget: "peak_memory_mb <= 6000".parse().unwrap(), actual: Some(7000.0) },
+            Outcome { budget: "swap_used == 0".parse().unwrap(), actual: Some(0.0) },
+        ];
+
+        let xml = generate_junit(&outcomes);
+
+        assert!(xml.contains("<testsuite name=\"budgets\" tests=\"2\" failures=\"1\">\n"));
+        assert!(xml.contains(
+            "<testcase classname=\"telemetry.budgets\" name=\"peak_memory_mb &lt;= 6000\">\n      \
+             <failure message=\"peak_memory_mb was 7000 MB, expected peak_memory_mb &lt;= 6000\"/>\n"
+        ));
+        assert!(xml.contains("<testcase classname=\"telemetry.budgets\" name=\"swap_used == 0\"/>\n"));
+    }
+}
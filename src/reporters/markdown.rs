@@ -1,43 +1,287 @@
 use anyhow::Result;
-use crate::collectors::{CpuStats, MemoryStats};
+use crate::collectors::{CpuStats, MemoryStats, ProcessStats, TracedProcess};
+use crate::data::Step;
+use crate::reporters::Summary;
+use std::collections::HashMap;
+
+/// Top processes テーブルに載せる最大プロセス数
+const MAX_TOP_PROCESSES: usize = 10;
+
+/// Gantt チャートに載せる最大プロセス数 (実行時間の長い順)
+const MAX_GANTT_PROCESSES: usize = 50;
+
+/// レポートに埋め込むチャート画像
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chart {
+    pub title: String,
+    /// 画像のURL (data URI を含む)
+    pub url: String,
+}
+
+impl Chart {
+    /// SVGをbase64のdata URIとして埋め込む
+    pub fn inline(title: &str, svg: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            url: format!("data:image/svg+xml;base64,{}", base64_encode(svg.as_bytes())),
+        }
+    }
+}
 
 /// Markdownレポートを生成
-pub fn generate_report(cpu_data: &[CpuStats], memory_data: &[MemoryStats]) -> Result<String> {
+///
+/// `charts` の先頭のチャートを冒頭に表示し、残りは折りたたんで表示する
+pub fn generate_report(
+    cpu_data: &[CpuStats],
+    memory_data: &[MemoryStats],
+    process_data: &[ProcessStats],
+    process_trace: &[TracedProcess],
+    steps: &[Step],
+    charts: &[Chart],
+) -> Result<String> {
     let mut report = String::new();
     
     // ヘッダー
     report.push_str("# Workflow Telemetry Report\n\n");
     
-    if cpu_data.is_empty() && memory_data.is_empty() {
+    let Some(summary) = Summary::from_series(cpu_data, memory_data) else {
         report.push_str("⚠️ No data collected\n\n");
         return Ok(report);
+    };
+
+    // チャート
+    if let Some((main, others)) = charts.split_first() {
+        report.push_str(&format!("![{}]({})\n\n", main.title, main.url));
+        if !others.is_empty() {
+            report.push_str("<details>\n<summary>More charts</summary>\n\n");
+            for chart in others {
+                report.push_str(&format!("![{}]({})\n\n", chart.title, chart.url));
+            }
+            report.push_str("</details>\n\n");
+        }
     }
     
     // CPUサマリー
     if !cpu_data.is_empty() {
-        let avg_cpu: f64 = cpu_data.iter().map(|s| s.total_load).sum::<f64>() / cpu_data.len() as f64;
-        let max_cpu: f64 = cpu_data.iter().map(|s| s.total_load).fold(0.0, f64::max);
-        
         report.push_str("## CPU Usage\n\n");
-        report.push_str(&format!("- **Average**: {:.2}%\n", avg_cpu));
-        report.push_str(&format!("- **Peak**: {:.2}%\n", max_cpu));
+        report.push_str(&format!("- **Average**: {:.2}%\n", summary.cpu_avg));
+        report.push_str(&format!("- **Peak**: {:.2}%\n", summary.cpu_peak));
         report.push_str(&format!("- **Data Points**: {}\n\n", cpu_data.len()));
     }
     
     // メモリサマリー
     if !memory_data.is_empty() {
-        let avg_mem: f64 = memory_data.iter().map(|s| s.used_mb as f64).sum::<f64>() / memory_data.len() as f64;
-        let max_mem: u64 = memory_data.iter().map(|s| s.used_mb).max().unwrap_or(0);
-        
         report.push_str("## Memory Usage\n\n");
-        report.push_str(&format!("- **Average**: {:.0} MB\n", avg_mem));
-        report.push_str(&format!("- **Peak**: {} MB\n", max_mem));
+        report.push_str(&format!("- **Average**: {:.0} MB ({:.2}%)\n", summary.memory_avg_mb, summary.memory_avg_percent));
+        report.push_str(&format!("- **Peak**: {} MB ({:.2}%)\n", summary.memory_peak_mb, summary.memory_peak_percent));
         report.push_str(&format!("- **Data Points**: {}\n\n", memory_data.len()));
     }
+
+    // ステップごとの統計
+    if !steps.is_empty() {
+        report.push_str(&generate_steps_table(steps, cpu_data, memory_data));
+    }
+
+    // 上位プロセス
+    if !process_data.is_empty() {
+        report.push_str(&generate_top_processes_table(process_data));
+    }
+
+    // プロセスのタイムライン
+    if !process_trace.is_empty() {
+        let trace_end = cpu_data.last().map(|s| s.time).unwrap_or(0);
+        report.push_str(&generate_process_gantt(process_trace, trace_end));
+    }
     
     Ok(report)
 }
 
+/// `telemetry mark` で区切ったステップごとのCPU・メモリの平均とピークのテーブルを生成
+///
+/// 記録終了時に実行中だったステップは最後のサンプルまでとして集計する
+fn generate_steps_table(steps: &[Step], cpu_data: &[CpuStats], memory_data: &[MemoryStats]) -> String {
+    let last_time = cpu_data.last().map(|s| s.time).unwrap_or(0);
+
+    let mut table = String::new();
+    table.push_str("## Steps\n\n");
+    table.push_str("| Step | Duration | CPU Avg | CPU Peak | Memory Avg | Memory Peak |\n");
+    table.push_str("|------|---------:|--------:|---------:|-----------:|------------:|\n");
+    for step in steps {
+        let in_step = |time: u64| time >= step.start_time && step.end_time.is_none_or(|end| time < end);
+        let cpu: Vec<f64> = cpu_data.iter().filter(|s| in_step(s.time)).map(|s| s.total_load).collect();
+        let memory: Vec<u64> = memory_data.iter().filter(|s| in_step(s.time)).map(|s| s.used_mb).collect();
+        let duration = step.end_time.unwrap_or(last_time).saturating_sub(step.start_time);
+
+        // ステップが短くサンプルがない場合は "-" を表示
+        let (cpu_avg, cpu_peak) = if cpu.is_empty() {
+            ("-".to_string(), "-".to_string())
+        } else {
+            (
+                format!("{:.1}%", cpu.iter().sum::<f64>() / cpu.len() as f64),
+                format!("{:.1}%", cpu.iter().copied().fold(0.0, f64::max)),
+            )
+        };
+        let (memory_avg, memory_peak) = match memory.iter().max() {
+            None => ("-".to_string(), "-".to_string()),
+            Some(max) => (
+                format!("{:.0} MB", memory.iter().sum::<u64>() as f64 / memory.len() as f64),
+                format!("{} MB", max),
+            ),
+        };
+
+        table.push_str(&format!(
+            "| {} | {:.1}s | {} | {} | {} | {} |\n",
+            escape_table_cell(&step.name),
+            duration as f64 / 1000.0,
+            cpu_avg,
+            cpu_peak,
+            memory_avg,
+            memory_peak,
+        ));
+    }
+    table.push('\n');
+
+    table
+}
+
+/// 全サンプルを通したプロセスごとのピーク値
+pub struct ProcessPeak<'a> {
+    pub pid: u32,
+    pub ppid: u32,
+    pub comm: &'a str,
+    pub cmdline: &'a str,
+    pub cpu_percent: f64,
+    pub rss_mb: u64,
+}
+
+/// CPU・メモリ上位として記録されたプロセスのピーク値 (CPUのピークが高い順)
+///
+/// PID が再利用されても別の行になるよう、PID と起動時刻の組で集計する。
+pub fn process_peaks(process_data: &[ProcessStats]) -> Vec<ProcessPeak<'_>> {
+    let mut peaks: HashMap<(u32, u64), ProcessPeak> = HashMap::new();
+    for process in process_data.iter().flat_map(|s| s.top_cpu.iter().chain(&s.top_memory)) {
+        let peak = peaks.entry((process.pid, process.start_time)).or_insert(ProcessPeak {
+            pid: process.pid,
+            ppid: process.ppid,
+            comm: &process.comm,
+            cmdline: &process.cmdline,
+            cpu_percent: 0.0,
+            rss_mb: 0,
+        });
+        peak.cpu_percent = peak.cpu_percent.max(process.cpu_percent);
+        peak.rss_mb = peak.rss_mb.max(process.rss_mb);
+    }
+
+    let mut peaks: Vec<ProcessPeak> = peaks.into_values().collect();
+    peaks.sort_by(|a, b| {
+        b.cpu_percent
+            .total_cmp(&a.cpu_percent)
+            .then(b.rss_mb.cmp(&a.rss_mb))
+    });
+    peaks
+}
+
+/// 全サンプルを通したプロセスごとのピーク値で Top processes テーブルを生成
+fn generate_top_processes_table(process_data: &[ProcessStats]) -> String {
+    let mut table = String::new();
+    table.push_str("## Top processes\n\n");
+    table.push_str("| PID | PPID | Process | Peak CPU | Peak RSS | Command |\n");
+    table.push_str("|----:|-----:|---------|---------:|---------:|---------|\n");
+    for peak in process_peaks(process_data).into_iter().take(MAX_TOP_PROCESSES) {
+        table.push_str(&format!(
+            "| {} | {} | {} | {:.1}% | {} MB | `{}` |\n",
+            peak.pid,
+            peak.ppid,
+            escape_table_cell(peak.comm),
+            peak.cpu_percent,
+            peak.rss_mb,
+            escape_table_cell(peak.cmdline),
+        ));
+    }
+    table.push('\n');
+
+    table
+}
+
+/// トレースしたプロセスを Mermaid の Gantt チャートで描画
+///
+/// トレース終了時点で実行中だったプロセスは `trace_end` (または最後に観測した時刻) までとして描く
+fn generate_process_gantt(process_trace: &[TracedProcess], trace_end: u64) -> String {
+    let trace_end = process_trace
+        .iter()
+        .flat_map(|p| [Some(p.start_time), p.end_time])
+        .flatten()
+        .fold(trace_end, u64::max);
+    let end_of = |p: &TracedProcess| p.end_time.unwrap_or(trace_end);
+
+    // 実行時間の長いものを選び、起動順に並べる
+    let mut processes: Vec<&TracedProcess> = process_trace.iter().collect();
+    processes.sort_by_key(|p| std::cmp::Reverse(end_of(p).saturating_sub(p.start_time)));
+    processes.truncate(MAX_GANTT_PROCESSES);
+    processes.sort_by_key(|p| (p.start_time, p.pid));
+
+    let mut chart = String::new();
+    chart.push_str("## Process Timeline\n\n");
+    if process_trace.len() > processes.len() {
+        chart.push_str(&format!(
+            "Showing the {} longest-running of {} traced processes.\n\n",
+            processes.len(),
+            process_trace.len()
+        ));
+    }
+    chart.push_str("```mermaid\n");
+    chart.push_str("gantt\n");
+    chart.push_str("    title Process Timeline\n");
+    chart.push_str("    dateFormat x\n");
+    chart.push_str("    axisFormat %H:%M:%S\n");
+    chart.push_str("    section Processes\n");
+    for (i, process) in processes.iter().enumerate() {
+        // 0ms のタスクは描画されないので最低1msにする
+        let end = end_of(process).max(process.start_time + 1);
+        let status = if process.end_time.is_some() { "done" } else { "active" };
+        chart.push_str(&format!(
+            "    {} ({}) :{}, p{}, {}, {}\n",
+            escape_gantt_label(&process.comm),
+            process.pid,
+            status,
+            i,
+            process.start_time,
+            end
+        ));
+    }
+    chart.push_str("```\n\n");
+
+    chart
+}
+
+/// Mermaid の Gantt タスク名で区切り文字として解釈される文字を置き換える
+fn escape_gantt_label(text: &str) -> String {
+    text.replace([':', ';', '#'], " ")
+}
+
+/// data URI 用の base64 エンコード (RFC 4648、パディングあり)
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Markdownテーブルのセルを壊す文字をエスケープ
+fn escape_table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('`', "'")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -50,12 +294,22 @@ mod tests {
                 total_load: 10.0,
                 user_load: 6.0,
                 system_load: 4.0,
+                iowait_load: 0.0,
+                irq_load: 0.0,
+                softirq_load: 0.0,
+                steal_load: 0.0,
+                per_core: vec![],
             },
             CpuStats {
                 time: 2000,
                 total_load: 20.0,
                 user_load: 12.0,
                 system_load: 8.0,
+                iowait_load: 0.0,
+                irq_load: 0.0,
+                softirq_load: 0.0,
+                steal_load: 0.0,
+                per_core: vec![],
             },
         ];
         
@@ -65,23 +319,179 @@ mod tests {
                 usage_percent: 50.0,
                 used_mb: 5000,
                 total_mb: 10000,
+                ..Default::default()
             },
         ];
 
-        let report = generate_report(&cpu_data, &memory_data).unwrap();
+        let svg = crate::charts::generate_combined_chart(&cpu_data, &memory_data).unwrap();
+        let charts = vec![
+            Chart::inline("CPU and Memory Usage", &svg),
+            Chart { title: "CPU Usage".to_string(), url: "https://example.com/cpu-usage.svg".to_string() },
+        ];
+        let report = generate_report(&cpu_data, &memory_data, &[], &[], &[], &charts).unwrap();
         
         assert!(report.contains("# Workflow Telemetry Report"));
         assert!(report.contains("CPU Usage"));
         assert!(report.contains("Memory Usage"));
-        assert!(report.contains("<svg"));
+        // SVGはdata URIとして埋め込まれる
+        let encoded = base64_encode(svg.as_bytes());
+        assert!(report.contains(&format!("![CPU and Memory Usage](data:image/svg+xml;base64,{})\n", encoded)));
+        assert!(report.contains("<summary>More charts</summary>\n\n![CPU Usage](https://example.com/cpu-usage.svg)\n"));
+    }
+
+    #[test]
+    fn test_base64_encode() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"M"), "TQ==");
+        assert_eq!(base64_encode(b"Ma"), "TWE=");
+        assert_eq!(base64_encode(b"Man"), "TWFu");
+        assert_eq!(base64_encode(b"<svg/>"), "PHN2Zy8+");
     }
 
     #[test]
     fn test_generate_report_empty_data() {
         let cpu_data: Vec<CpuStats> = vec![];
         let memory_data: Vec<MemoryStats> = vec![];
-        let report = generate_report(&cpu_data, &memory_data).unwrap();
+        let report = generate_report(&cpu_data, &memory_data, &[], &[], &[], &[]).unwrap();
         
         assert!(report.contains("No data collected"));
     }
+
+    #[test]
+    fn test_generate_report_top_processes() {
+        use crate::collectors::process::ProcessInfo;
+
+        let process = |pid: u32, cpu_percent: f64, rss_mb: u64| ProcessInfo {
+            pid,
+            ppid: 1,
+            start_time: 0,
+            comm: format!("proc{}", pid),
+            cmdline: format!("proc{} --flag a|b", pid),
+            cpu_percent,
+            rss_mb,
+        };
+        let cpu_data = vec![CpuStats {
+            time: 1000,
+            total_load: 50.0,
+            user_load: 40.0,
+            system_load: 10.0,
+            iowait_load: 0.0,
+            irq_load: 0.0,
+            softirq_load: 0.0,
+            steal_load: 0.0,
+            per_core: vec![],
+        }];
+        let process_data = vec![
+            ProcessStats {
+                time: 1000,
+                top_cpu: vec![process(10, 20.0, 100)],
+                top_memory: vec![process(20, 1.0, 900)],
+            },
+            ProcessStats {
+                time: 2000,
+                top_cpu: vec![process(10, 95.0, 120)],
+                top_memory: vec![process(20, 2.0, 950)],
+            },
+        ];
+
+        let report = generate_report(&cpu_data, &[], &process_data, &[], &[], &[]).unwrap();
+
+        assert!(report.contains("## Top processes"));
+        assert!(report.contains("| 10 | 1 | proc10 | 95.0% | 120 MB | `proc10 --flag a\\|b` |"));
+        assert!(report.contains("| 20 | 1 | proc20 | 2.0% | 950 MB |"));
+        // CPUピークの高い順に並ぶ
+        assert!(report.find("proc10").unwrap() < report.find("proc20").unwrap());
+    }
+
+    #[test]
+    fn test_process_peaks_reused_pid() {
+        use crate::collectors::process::ProcessInfo;
+
+        let process = |start_time: u64, comm: &str, cpu_percent: f64| ProcessInfo {
+            pid: 10,
+            ppid: 1,
+            start_time,
+            comm: comm.to_string(),
+            cmdline: String::new(),
+            cpu_percent,
+            rss_mb: 100,
+        };
+        let process_data = vec![
+            ProcessStats { time: 1000, top_cpu: vec![process(500, "make", 80.0)], top_memory: vec![] },
+            ProcessStats { time: 2000, top_cpu: vec![process(500, "make", 90.0)], top_memory: vec![] },
+            // 同じ PID が別のコマンドに再利用された
+            ProcessStats { time: 3000, top_cpu: vec![process(900, "cc1", 40.0)], top_memory: vec![] },
+        ];
+
+        let peaks = process_peaks(&process_data);
+
+        assert_eq!(peaks.len(), 2);
+        assert_eq!((peaks[0].comm, peaks[0].cpu_percent), ("make", 90.0));
+        assert_eq!((peaks[1].comm, peaks[1].cpu_percent), ("cc1", 40.0));
+    }
+
+    #[test]
+    fn test_generate_report_process_gantt() {
+        let traced = |pid: u32, comm: &str, start_time: u64, end_time: Option<u64>| TracedProcess {
+            pid,
+            ppid: 1,
+            comm: comm.to_string(),
+            cmdline: String::new(),
+            start_time,
+            end_time,
+        };
+        let cpu_data = vec![CpuStats {
+            time: 9000,
+            total_load: 50.0,
+            user_load: 40.0,
+            system_load: 10.0,
+            iowait_load: 0.0,
+            irq_load: 0.0,
+            softirq_load: 0.0,
+            steal_load: 0.0,
+            per_core: vec![],
+        }];
+        let process_trace = vec![
+            traced(200, "cargo", 1000, Some(8000)),
+            traced(201, "rustc: build", 2000, None),
+        ];
+
+        let report = generate_report(&cpu_data, &[], &[], &process_trace, &[], &[]).unwrap();
+
+        assert!(report.contains("```mermaid\ngantt\n"));
+        assert!(report.contains("    cargo (200) :done, p0, 1000, 8000\n"));
+        // 実行中のプロセスは最後のサンプル時刻まで描く
+        assert!(report.contains("    rustc  build (201) :active, p1, 2000, 9000\n"));
+    }
+
+    #[test]
+    fn test_generate_report_steps() {
+        let cpu = |time: u64, total_load: f64| CpuStats {
+            time,
+            total_load,
+            user_load: total_load,
+            system_load: 0.0,
+            iowait_load: 0.0,
+            irq_load: 0.0,
+            softirq_load: 0.0,
+            steal_load: 0.0,
+            per_core: vec![],
+        };
+        let memory = |time: u64, used_mb: u64| MemoryStats { time, used_mb, ..Default::default() };
+        let cpu_data = vec![cpu(1000, 10.0), cpu(2000, 30.0), cpu(3000, 80.0), cpu(4000, 60.0)];
+        let memory_data = vec![memory(1000, 100), memory(2000, 300), memory(3000, 900), memory(4000, 700)];
+        let steps = vec![
+            Step { name: "build".to_string(), start_time: 1000, end_time: Some(3000) },
+            Step { name: "setup".to_string(), start_time: 3000, end_time: Some(3500) },
+            Step { name: "test".to_string(), start_time: 3500, end_time: None },
+        ];
+
+        let report = generate_report(&cpu_data, &memory_data, &[], &[], &steps, &[]).unwrap();
+
+        assert!(report.contains("## Steps"));
+        assert!(report.contains("| build | 2.0s | 20.0% | 30.0% | 200 MB | 300 MB |\n"));
+        assert!(report.contains("| setup | 0.5s | 80.0% | 80.0% | 900 MB | 900 MB |\n"));
+        // 実行中のステップは最後のサンプルまで
+        assert!(report.contains("| test | 0.5s | 60.0% | 60.0% | 700 MB | 700 MB |\n"));
+    }
 }
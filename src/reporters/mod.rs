@@ -1,3 +1,18 @@
+pub mod comment;
+pub mod comparison;
+pub mod csv;
+pub mod github;
+pub mod html;
+pub mod junit;
 pub mod markdown;
+pub mod openmetrics;
+pub mod summary;
 
-pub use markdown::generate_report;
+pub use comment::generate_comment;
+pub use comparison::{comparison_table, highlights};
+pub use csv::generate_csv;
+pub use html::generate_html;
+pub use junit::generate_junit;
+pub use markdown::{generate_report, Chart};
+pub use openmetrics::{current_openmetrics, generate_openmetrics};
+pub use summary::{Exceeded, Summary};
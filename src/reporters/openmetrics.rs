@@ -0,0 +1,323 @@
+use crate::data::TelemetryData;
+use crate::sample_log::Sample;
+
+/// メトリクス名の接頭辞
+const NAMESPACE: &str = "workflow_telemetry";
+
+/// MB (記録データの単位) をバイトに換算する倍率
+const MB: f64 = 1024.0 * 1024.0;
+
+/// 1つのメトリクスファミリー (すべてゲージ)
+struct Family {
+    /// 接頭辞を除いた名前
+    name: &'static str,
+    help: &'static str,
+    /// `# UNIT` に書く単位 (名前の末尾と同じにする)。空なら書かない
+    unit: &'static str,
+    points: Vec<Point>,
+}
+
+/// 1つの値 (時刻はミリ秒)
+struct Point {
+    labels: Vec<(&'static str, String)>,
+    time: u64,
+    value: f64,
+}
+
+/// 記録データ全体を、サンプルごとにタイムスタンプを付けた OpenMetrics テキストにする
+///
+/// `promtool tsdb create-blocks-from openmetrics` で Prometheus のブロックに取り込める。
+pub fn generate_openmetrics(data: &TelemetryData) -> String {
+    render(&families(data), true)
+}
+
+/// 1回のサンプルを、タイムスタンプなしの OpenMetrics テキストにする (`/metrics` の応答)
+pub fn current_openmetrics(sample: &Sample) -> String {
+    let data = TelemetryData {
+        cpu: sample.cpu.iter().cloned().collect(),
+        memory: sample.memory.iter().cloned().collect(),
+        network: sample.network.iter().cloned().collect(),
+        disk: sample.disk.iter().cloned().collect(),
+        pressure: sample.pressure.iter().cloned().collect(),
+        cgroup: sample.cgroup.iter().cloned().collect(),
+        ..Default::default()
+    };
+    render(&families(&data), false)
+}
+
+/// 記録されている系列のメトリクス
+fn families(data: &TelemetryData) -> Vec<Family> {
+    let mut families = Vec::new();
+
+    let cpu = &data.cpu;
+    gauge(
+        &mut families,
+        ("cpu_usage_percent", "CPU usage across all cores.", "percent"),
+        cpu.iter().map(|s| point(s.time, s.total_load)),
+    );
+    gauge(
+        &mut families,
+        ("cpu_mode_usage_percent", "CPU usage by mode across all cores.", "percent"),
+        cpu.iter().flat_map(|s| {
+            [
+                ("user", s.user_load),
+                ("system", s.system_load),
+                ("iowait", s.iowait_load),
+                ("irq", s.irq_load),
+                ("softirq", s.softirq_load),
+                ("steal", s.steal_load),
+            ]
+            .map(|(mode, load)| labeled(s.time, load, "mode", mode))
+        }),
+    );
+    gauge(
+        &mut families,
+        ("cpu_core_usage_percent", "CPU usage of each core.", "percent"),
+        cpu.iter().flat_map(|s| {
+            s.per_core.iter().enumerate().map(|(core, load)| labeled(s.time, *load, "core", &core.to_string()))
+        }),
+    );
+
+    let memory = &data.memory;
+    gauge(
+        &mut families,
+        ("memory_usage_percent", "Memory usage.", "percent"),
+        memory.iter().map(|s| point(s.time, s.usage_percent)),
+    );
+    gauge(
+        &mut families,
+        ("memory_used_bytes", "Memory in use.", "bytes"),
+        memory.iter().map(|s| point(s.time, s.used_mb as f64 * MB)),
+    );
+    gauge(
+        &mut families,
+        ("memory_total_bytes", "Total memory.", "bytes"),
+        memory.iter().map(|s| point(s.time, s.total_mb as f64 * MB)),
+    );
+    gauge(
+        &mut families,
+        ("memory_limit_bytes", "Memory limit of the cgroup.", "bytes"),
+        memory.iter().filter_map(|s| s.limit_mb.map(|mb| point(s.time, mb as f64 * MB))),
+    );
+    gauge(
+        &mut families,
+        ("memory_cached_bytes", "Memory used by the page cache.", "bytes"),
+        memory.iter().map(|s| point(s.time, s.cached_mb as f64 * MB)),
+    );
+    gauge(
+        &mut families,
+        ("swap_used_bytes", "Swap in use.", "bytes"),
+        memory.iter().map(|s| point(s.time, s.swap_used_mb as f64 * MB)),
+    );
+
+    let network = &data.network;
+    gauge(
+        &mut families,
+        ("network_receive_bytes_per_second", "Bytes received per second on all interfaces.", ""),
+        network.iter().map(|s| point(s.time, s.rx_bytes_per_sec)),
+    );
+    gauge(
+        &mut families,
+        ("network_transmit_bytes_per_second", "Bytes sent per second on all interfaces.", ""),
+        network.iter().map(|s| point(s.time, s.tx_bytes_per_sec)),
+    );
+
+    let devices = || data.disk.iter().flat_map(|s| s.devices.iter().map(move |device| (s.time, device)));
+    gauge(
+        &mut families,
+        ("disk_read_bytes_per_second", "Bytes read per second.", ""),
+        devices().map(|(time, d)| labeled(time, d.read_bytes_per_sec, "device", &d.name)),
+    );
+    gauge(
+        &mut families,
+        ("disk_write_bytes_per_second", "Bytes written per second.", ""),
+        devices().map(|(time, d)| labeled(time, d.write_bytes_per_sec, "device", &d.name)),
+    );
+    gauge(
+        &mut families,
+        ("disk_busy_percent", "Share of time the device was busy.", "percent"),
+        devices().map(|(time, d)| labeled(time, d.busy_percent, "device", &d.name)),
+    );
+
+    let resources = || {
+        data.pressure
+            .iter()
+            .flat_map(|s| [("cpu", &s.cpu), ("memory", &s.memory), ("io", &s.io)].map(|(name, p)| (s.time, name, p)))
+    };
+    gauge(
+        &mut families,
+        ("pressure_some_avg10_percent", "Share of time some tasks stalled on the resource (10s average).", "percent"),
+        resources().map(|(time, name, p)| labeled(time, p.some_avg10, "resource", name)),
+    );
+    gauge(
+        &mut families,
+        ("pressure_full_avg10_percent", "Share of time all tasks stalled on the resource (10s average).", "percent"),
+        resources().map(|(time, name, p)| labeled(time, p.full_avg10, "resource", name)),
+    );
+
+    let cgroup = &data.cgroup;
+    gauge(
+        &mut families,
+        ("cgroup_memory_current_bytes", "Memory charged to the job's cgroup.", "bytes"),
+        cgroup.iter().map(|s| point(s.time, s.memory_current_mb as f64 * MB)),
+    );
+    gauge(
+        &mut families,
+        ("cgroup_cpu_quota_cores", "CPU quota of the job's cgroup in cores.", ""),
+        cgroup.iter().filter_map(|s| s.cpu_quota_cores.map(|cores| point(s.time, cores))),
+    );
+
+    families
+}
+
+/// 値があればファミリーを追加
+///
+/// 値はサンプルの順に受け取り、同じラベルの値を時刻順のまま続けて並べ直す
+/// (OpenMetrics では1つの系列の値を分けて書けない)。ラベルは最初に現れた順に並べる。
+fn gauge(
+    families: &mut Vec<Family>,
+    (name, help, unit): (&'static str, &'static str, &'static str),
+    points: impl IntoIterator<Item = Point>,
+) {
+    let mut labelsets: Vec<Vec<(&'static str, String)>> = Vec::new();
+    let mut points: Vec<(usize, Point)> = points
+        .into_iter()
+        .map(|point| match labelsets.iter().position(|labels| *labels == point.labels) {
+            Some(index) => (index, point),
+            None => {
+                labelsets.push(point.labels.clone());
+                (labelsets.len() - 1, point)
+            }
+        })
+        .collect();
+    points.sort_by_key(|(index, _)| *index);
+
+    if !points.is_empty() {
+        families.push(Family { name, help, unit, points: points.into_iter().map(|(_, point)| point).collect() });
+    }
+}
+
+fn point(time: u64, value: f64) -> Point {
+    Point { labels: Vec::new(), time, value }
+}
+
+fn labeled(time: u64, value: f64, label: &'static str, label_value: &str) -> Point {
+    Point { labels: vec![(label, label_value.to_string())], time, value }
+}
+
+/// テキスト形式にする (`timestamps` ならエポックからの秒を付ける)
+fn render(families: &[Family], timestamps: bool) -> String {
+    let mut text = String::new();
+    for family in families {
+        let name = format!("{}_{}", NAMESPACE, family.name);
+        text.push_str(&format!("# TYPE {} gauge\n", name));
+        if !family.unit.is_empty() {
+            text.push_str(&format!("# UNIT {} {}\n", name, family.unit));
+        }
+        text.push_str(&format!("# HELP {} {}\n", name, family.help));
+
+        for point in &family.points {
+            text.push_str(&name);
+            if !point.labels.is_empty() {
+                let labels: Vec<String> = point
+                    .labels
+                    .iter()
+                    .map(|(label, value)| format!("{}=\"{}\"", label, escape_label(value)))
+                    .collect();
+                text.push_str(&format!("{{{}}}", labels.join(",")));
+            }
+            text.push_str(&format!(" {}", format_value(point.value)));
+            if timestamps {
+                text.push_str(&format!(" {}.{:03}", point.time / 1000, point.time % 1000));
+            }
+            text.push('\n');
+        }
+    }
+    text.push_str("# EOF\n");
+    text
+}
+
+/// ラベル値のエスケープ
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// 値の表記 (無限大と非数は OpenMetrics の表記にする)
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::disk::DiskDeviceStats;
+    use crate::collectors::{CpuStats, DiskStats, MemoryStats};
+
+    #[test]
+    fn test_generate_openmetrics() {
+        let cpu = |time: u64, total_load: f64| CpuStats {
+            time,
+            total_load,
+            user_load: total_load,
+            system_load: 0.0,
+            iowait_load: 0.0,
+            irq_load: 0.0,
+            softirq_load: 0.0,
+            steal_load: 0.0,
+            per_core: vec![total_load, 0.5],
+        };
+        let device = |name: &str, read: f64| DiskDeviceStats {
+            name: name.to_string(),
+            read_bytes_per_sec: read,
+            write_bytes_per_sec: 0.0,
+            read_iops: 0.0,
+            write_iops: 0.0,
+            busy_percent: 0.0,
+        };
+        let data = TelemetryData {
+            cpu: vec![cpu(1_700_000_000_005, 12.5), cpu(1_700_000_002_010, f64::NAN)],
+            memory: vec![MemoryStats { time: 1_700_000_000_007, used_mb: 2, ..Default::default() }],
+            disk: vec![
+                DiskStats { time: 1_700_000_000_009, devices: vec![device("sda", 10.0)] },
+                DiskStats { time: 1_700_000_002_009, devices: vec![device("nvme\"0", 30.0), device("sda", 20.0)] },
+            ],
+            ..Default::default()
+        };
+
+        let text = generate_openmetrics(&data);
+        assert!(text.starts_with(
+            "# TYPE workflow_telemetry_cpu_usage_percent gauge\n\
+             # UNIT workflow_telemetry_cpu_usage_percent percent\n\
+             # HELP workflow_telemetry_cpu_usage_percent CPU usage across all cores.\n\
+             workflow_telemetry_cpu_usage_percent 12.5 1700000000.005\n\
+             workflow_telemetry_cpu_usage_percent NaN 1700000002.010\n"
+        ));
+        // 同じラベルの値は続けて並べる
+        assert!(text.contains(
+            "workflow_telemetry_cpu_core_usage_percent{core=\"0\"} 12.5 1700000000.005\n\
+             workflow_telemetry_cpu_core_usage_percent{core=\"0\"} NaN 1700000002.010\n\
+             workflow_telemetry_cpu_core_usage_percent{core=\"1\"} 0.5 1700000000.005\n"
+        ));
+        assert!(text.contains("workflow_telemetry_memory_used_bytes 2097152 1700000000.007\n"));
+        assert!(text.contains(
+            "workflow_telemetry_disk_read_bytes_per_second{device=\"sda\"} 20 1700000002.009\n\
+             workflow_telemetry_disk_read_bytes_per_second{device=\"nvme\\\"0\"} 30 1700000002.009\n"
+        ));
+        // 記録されていない系列は出力しない
+        assert!(!text.contains("network"));
+        assert!(!text.contains("memory_limit_bytes"));
+        assert!(text.ends_with("\n# EOF\n"));
+
+        let sample = Sample { cpu: Some(cpu(1_700_000_000_005, 40.0)), ..Default::default() };
+        let text = current_openmetrics(&sample);
+        assert!(text.contains("\nworkflow_telemetry_cpu_usage_percent 40\n"));
+        assert!(!text.contains("memory"));
+        assert_eq!(current_openmetrics(&Sample::default()), "# EOF\n");
+    }
+}
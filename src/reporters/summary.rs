@@ -0,0 +1,175 @@
+use crate::collectors::{CpuStats, MemoryStats};
+use crate::config::Thresholds;
+use crate::data::TelemetryData;
+
+/// 記録全体の統計値
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    /// データポイント数
+    pub samples: usize,
+    /// 最初と最後のサンプルの間隔 (秒)
+    pub duration_secs: u64,
+    pub cpu_avg: f64,
+    pub cpu_peak: f64,
+    pub memory_avg_percent: f64,
+    pub memory_peak_percent: f64,
+    pub memory_avg_mb: f64,
+    pub memory_peak_mb: u64,
+}
+
+/// 閾値を超えた項目
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exceeded {
+    /// エラーの閾値を超えたか (falseなら警告)
+    pub error: bool,
+    pub message: String,
+}
+
+impl Summary {
+    /// 記録データから統計値を計算 (CPUとメモリのサンプルがなければNone)
+    pub fn from_data(data: &TelemetryData) -> Option<Self> {
+        Self::from_series(&data.cpu, &data.memory)
+    }
+
+    /// CPUとメモリの系列から統計値を計算 (データポイント数と記録時間はCPU、なければメモリから数える)
+    pub fn from_series(cpu: &[CpuStats], memory: &[MemoryStats]) -> Option<Self> {
+        let times: Vec<u64> = if cpu.is_empty() {
+            memory.iter().map(|s| s.time).collect()
+        } else {
+            cpu.iter().map(|s| s.time).collect()
+        };
+        let (first, last) = (times.first()?, times.last()?);
+        let cpu_loads: Vec<f64> = cpu.iter().map(|s| s.total_load).collect();
+        let memory_percents: Vec<f64> = memory.iter().map(|s| s.usage_percent).collect();
+        let memory_mb: Vec<f64> = memory.iter().map(|s| s.used_mb as f64).collect();
+
+        Some(Self {
+            samples: times.len(),
+            duration_secs: last.saturating_sub(*first) / 1000,
+            cpu_avg: average(&cpu_loads),
+            cpu_peak: peak(&cpu_loads),
+            memory_avg_percent: average(&memory_percents),
+            memory_peak_percent: peak(&memory_percents),
+            memory_avg_mb: average(&memory_mb),
+            memory_peak_mb: memory.iter().map(|s| s.used_mb).max().unwrap_or(0),
+        })
+    }
+
+    /// 閾値を超えた項目を返す
+    ///
+    /// エラーの閾値を超えた項目は警告の閾値を超えていても1件だけ (エラーとして) 返す
+    pub fn exceeded(&self, thresholds: &Thresholds) -> Vec<Exceeded> {
+        let metrics = [
+            ("CPU peak", self.cpu_peak, thresholds.cpu_percent, thresholds.cpu_error_percent),
+            ("Memory peak", self.memory_peak_percent, thresholds.memory_percent, thresholds.memory_error_percent),
+        ];
+
+        let mut exceeded = Vec::new();
+        for (name, value, warning, error) in metrics {
+            let (error, limit) = match (error, warning) {
+                (Some(limit), _) if value > limit => (true, limit),
+                (_, Some(limit)) if value > limit => (false, limit),
+                _ => continue,
+            };
+            exceeded.push(Exceeded {
+                error,
+                message: format!("{} {:.2}% exceeds threshold {:.2}%", name, value, limit),
+            });
+        }
+        exceeded
+    }
+
+    /// Markdownのリストとして出力
+    pub fn to_markdown(&self) -> String {
+        let mut text = String::new();
+        text.push_str(&format!("- **Data Points**: {}\n", self.samples));
+        text.push_str(&format!("- **Duration**: {}s\n", self.duration_secs));
+        text.push_str(&format!("- **CPU Average**: {:.2}%\n", self.cpu_avg));
+        text.push_str(&format!("- **CPU Peak**: {:.2}%\n", self.cpu_peak));
+        text.push_str(&format!("- **Memory Average**: {:.2}%\n", self.memory_avg_percent));
+        text.push_str(&format!(
+            "- **Memory Peak**: {:.2}% ({} MB)\n",
+            self.memory_peak_percent, self.memory_peak_mb
+        ));
+        text
+    }
+}
+
+fn average(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn peak(values: &[f64]) -> f64 {
+    values.iter().copied().fold(0.0, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data() -> TelemetryData {
+        let cpu = |time: u64, total_load: f64| CpuStats {
+            time,
+            total_load,
+            user_load: total_load,
+            system_load: 0.0,
+            iowait_load: 0.0,
+            irq_load: 0.0,
+            softirq_load: 0.0,
+            steal_load: 0.0,
+            per_core: vec![],
+        };
+        let memory = |time: u64, used_mb: u64| MemoryStats {
+            time,
+            usage_percent: used_mb as f64 / 10.0,
+            used_mb,
+            total_mb: 1000,
+            ..Default::default()
+        };
+        TelemetryData {
+            cpu: vec![cpu(1000, 10.0), cpu(6000, 50.0), cpu(11000, 30.0)],
+            memory: vec![memory(1000, 200), memory(6000, 800), memory(11000, 500)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_summary_from_data() {
+        let summary = Summary::from_data(&data()).unwrap();
+
+        assert_eq!(summary.samples, 3);
+        assert_eq!(summary.duration_secs, 10);
+        assert_eq!(summary.cpu_avg, 30.0);
+        assert_eq!(summary.cpu_peak, 50.0);
+        assert_eq!(summary.memory_avg_percent, 50.0);
+        assert_eq!(summary.memory_peak_percent, 80.0);
+        assert_eq!(summary.memory_avg_mb, 500.0);
+        assert_eq!(summary.memory_peak_mb, 800);
+        assert!(summary.to_markdown().contains("- **CPU Peak**: 50.00%\n"));
+
+        assert!(Summary::from_data(&TelemetryData::default()).is_none());
+    }
+
+    #[test]
+    fn test_summary_exceeded() {
+        let summary = Summary::from_data(&data()).unwrap();
+        let thresholds = Thresholds {
+            cpu_percent: Some(40.0),
+            memory_percent: Some(70.0),
+            memory_error_percent: Some(75.0),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            summary.exceeded(&thresholds),
+            vec![
+                Exceeded { error: false, message: "CPU peak 50.00% exceeds threshold 40.00%".to_string() },
+                Exceeded { error: true, message: "Memory peak 80.00% exceeds threshold 75.00%".to_string() },
+            ]
+        );
+        assert!(summary.exceeded(&Thresholds::default()).is_empty());
+    }
+}
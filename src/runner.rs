@@ -0,0 +1,165 @@
+use crate::collectors::ProcessCollector;
+use crate::data::Step;
+use chrono::NaiveDateTime;
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// GitHub Actions ランナーの診断ログからワークフローのステップを読む
+///
+/// `diag_dir` を省略すると、祖先プロセスの `Runner.Worker` からランナーの `_diag` ディレクトリを探す。
+/// ランナーの外で実行した場合やログが見つからない場合は空を返す。
+pub fn read_steps(diag_dir: Option<&Path>) -> Vec<Step> {
+    let Some(dir) = diag_dir.map(Path::to_path_buf).or_else(find_diag_dir) else {
+        return Vec::new();
+    };
+    let Some(log) = latest_worker_log(&dir) else {
+        return Vec::new();
+    };
+    match fs::read_to_string(&log) {
+        Ok(content) => parse_worker_log(&content),
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", log.display(), e);
+            Vec::new()
+        }
+    }
+}
+
+/// 記録期間 (`start` から `end` までのミリ秒) と重なるステップだけを残す
+///
+/// 記録を始める前に終わったステップ (ジョブのセットアップなど) と、記録の終了後に始まったステップを除く。
+/// 診断ログは最新のものを読むので、過去の記録に対しては別のジョブのステップがすべて除かれる。
+pub fn overlapping(steps: Vec<Step>, start: u64, end: u64) -> Vec<Step> {
+    steps
+        .into_iter()
+        .filter(|step| step.start_time <= end && step.end_time.is_none_or(|step_end| step_end > start))
+        .collect()
+}
+
+/// 祖先プロセスの `<runner>/bin/Runner.Worker` から `<runner>/_diag` を求める
+fn find_diag_dir() -> Option<PathBuf> {
+    let mut pid = std::process::id();
+    while pid > 1 {
+        if let Ok(exe) = fs::read_link(format!("/proc/{}/exe", pid)) {
+            if exe.file_name().is_some_and(|name| name == "Runner.Worker") {
+                return exe.parent()?.parent().map(|root| root.join("_diag"));
+            }
+        }
+        let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+        pid = ProcessCollector::parse_stat(&stat)?.ppid;
+    }
+    None
+}
+
+/// 最後に更新された `Worker_*.log` (セルフホストランナーでは過去のジョブのログも残っている)
+fn latest_worker_log(dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("Worker_") && name.ends_with(".log")
+        })
+        .max_by_key(|entry| entry.metadata().and_then(|metadata| metadata.modified()).ok())
+        .map(|entry| entry.path())
+}
+
+/// Worker ログの StepsRunner の出力からステップを組み立てる
+///
+/// `Processing step: DisplayName='...'` を開始、`Step result: ...` または次のステップの開始を終了とする。
+/// 条件により実行されなかったステップ (`Skipping step ...`) は含めない。時刻は秒単位 (UTC)。
+fn parse_worker_log(content: &str) -> Vec<Step> {
+    let line_pattern = Regex::new(r"^\[(\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2})Z \w+ [\w.]+\] (.*)$").unwrap();
+    let mut steps: Vec<Step> = Vec::new();
+
+    for line in content.lines() {
+        let Some(captures) = line_pattern.captures(line.trim_end()) else {
+            continue;
+        };
+        let Ok(time) = NaiveDateTime::parse_from_str(&captures[1], "%Y-%m-%d %H:%M:%S") else {
+            continue;
+        };
+        let time = time.and_utc().timestamp_millis() as u64;
+        let message = &captures[2];
+
+        let open = steps.last_mut().filter(|step| step.end_time.is_none());
+        if let Some(name) = message.strip_prefix("Processing step: DisplayName='") {
+            if let Some(step) = open {
+                step.end_time = Some(time);
+            }
+            steps.push(Step {
+                name: name.strip_suffix('\'').unwrap_or(name).to_string(),
+                start_time: time,
+                end_time: None,
+            });
+        } else if message.starts_with("Skipping step") {
+            if open.is_some() {
+                steps.pop();
+            }
+        } else if message.starts_with("Step result:") {
+            if let Some(step) = open {
+                step.end_time = Some(time);
+            }
+        }
+    }
+
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_worker_log() {
+        let content = "\
+[2024-05-01 10:00:00Z INFO JobRunner] Job ID 1234
+[2024-05-01 10:00:01Z INFO StepsRunner] Processing step: DisplayName='Run actions/checkout@v4'
+[2024-05-01 10:00:01Z INFO StepsRunner] Starting the step.
+[2024-05-01 10:00:03Z INFO StepsRunner] Step result: Succeeded
+[2024-05-01 10:00:03Z INFO StepsRunner] Processing step: DisplayName='Deploy'
+[2024-05-01 10:00:03Z INFO StepsRunner] Skipping step due to condition evaluation.
+[2024-05-01 10:00:04Z INFO StepsRunner] Processing step: DisplayName='Run cargo test'\r
+[2024-05-01 10:00:04Z INFO StepsRunner] Starting the step.
+[2024-05-01 10:01:04Z INFO StepsRunner] Step result: Failed
+[2024-05-01 10:01:05Z INFO StepsRunner] Processing step: DisplayName='Post Run actions/checkout@v4'
+";
+        let steps = parse_worker_log(content);
+        let base = 1714557600000;
+
+        assert_eq!(
+            steps,
+            vec![
+                Step {
+                    name: "Run actions/checkout@v4".to_string(),
+                    start_time: base + 1000,
+                    end_time: Some(base + 3000),
+                },
+                Step { name: "Run cargo test".to_string(), start_time: base + 4000, end_time: Some(base + 64000) },
+                // 実行中のステップは終了時刻なし
+                Step { name: "Post Run actions/checkout@v4".to_string(), start_time: base + 65000, end_time: None },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let step = |start_time: u64, end_time: Option<u64>| Step { name: format!("{}", start_time), start_time, end_time };
+        let steps = vec![
+            step(10_000, Some(11_000)),
+            step(11_000, Some(15_000)),
+            step(15_000, Some(19_000)),
+            step(19_000, Some(19_500)),
+            step(19_500, None),
+        ];
+        let names = |steps: Vec<Step>| steps.into_iter().map(|step| step.name).collect::<Vec<_>>();
+
+        // 記録の前に終わったステップと、記録の後に始まったステップは除く
+        assert_eq!(names(overlapping(steps.clone(), 11_000, 19_000)), vec!["11000", "15000", "19000"]);
+        // 実行中のステップは記録の終わりまで続いているとみなす
+        assert_eq!(names(overlapping(steps.clone(), 19_600, 30_000)), vec!["19500"]);
+        // 以前のジョブの記録には、最新の診断ログのステップを付けない
+        assert!(overlapping(steps, 1_000, 9_000).is_empty());
+    }
+}
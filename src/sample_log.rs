@@ -0,0 +1,244 @@
+use crate::collectors::{
+    CgroupStats, CpuStats, DiskStats, MemoryStats, NetworkStats, PressureStats, ProcessStats,
+    TraceEvent,
+};
+use crate::control::Mark;
+use crate::data::{CommandRun, Step, TelemetryData};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+/// 1回のサンプリングで収集した値 (取得に失敗した系列はNone)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Sample {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<CpuStats>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<MemoryStats>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<NetworkStats>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disk: Option<DiskStats>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pressure: Option<PressureStats>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cgroup: Option<CgroupStats>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub processes: Option<ProcessStats>,
+}
+
+/// サンプルログの1行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogRecord {
+    Sample(Box<Sample>),
+    Trace(TraceEvent),
+    /// `telemetry run` で実行したコマンドの結果 (コマンド終了時に1回だけ書く)
+    Command(CommandRun),
+    /// `telemetry mark` で受け取ったステップの区切り
+    Mark(Mark),
+}
+
+/// 追記専用の NDJSON サンプルログ
+///
+/// 1行に1レコードを書き、サンプルごとに fsync するので、記録中に
+/// プロセスが強制終了されてもそれまでのサンプルは失われない。
+pub struct SampleLog {
+    file: File,
+}
+
+impl SampleLog {
+    /// ログファイルを新規作成 (既存のファイルは切り詰める)
+    pub fn create(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        Ok(Self { file })
+    }
+
+    /// レコードを1行追記
+    pub fn append(&mut self, record: &LogRecord) -> Result<()> {
+        let mut line = serde_json::to_vec(record).context("Failed to serialize log record")?;
+        line.push(b'\n');
+        // 1回の write で書くことで、途中で落ちても壊れるのは最終行だけになる
+        self.file.write_all(&line).context("Failed to append log record")?;
+
+        // サンプルとマーカーは書くたびにディスクへ同期する (トレースイベントは次のサンプルでまとめて同期される)
+        if matches!(record, LogRecord::Sample(_) | LogRecord::Mark(_)) {
+            self.sync()?;
+        }
+        Ok(())
+    }
+
+    /// 書き込み済みの内容をディスクへ同期
+    pub fn sync(&mut self) -> Result<()> {
+        self.file.sync_data().context("Failed to sync log file")
+    }
+}
+
+/// 記録データを読み込む
+///
+/// 従来の JSON 形式と NDJSON 形式のサンプルログの両方に対応する。
+/// NDJSON は途中で切れていてもパースできた行までを返す。
+pub fn read_telemetry(path: &Path) -> Result<TelemetryData> {
+    let content = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    // 書き込み途中で強制終了されると最終行がマルチバイト文字の途中で切れるので、不正なバイトは置き換えて読む
+    let content = String::from_utf8_lossy(&content);
+
+    if let Ok(data) = serde_json::from_str::<TelemetryData>(&content) {
+        return Ok(data);
+    }
+
+    let data = parse_sample_log(&content);
+    if data.cpu.is_empty()
+        && data.memory.is_empty()
+        && data.process_trace.is_empty()
+        && data.steps.is_empty()
+        && data.command.is_none()
+    {
+        anyhow::bail!("No telemetry records found in {}", path.display());
+    }
+    Ok(data)
+}
+
+/// NDJSON のサンプルログを TelemetryData に変換 (壊れた行は読み飛ばす)
+fn parse_sample_log(content: &str) -> TelemetryData {
+    let mut data = TelemetryData::default();
+    // (pid, 起動時刻) → process_trace のインデックス
+    let mut traced: HashMap<(u32, u64), usize> = HashMap::new();
+    let mut skipped = 0;
+
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        let record = match serde_json::from_str::<LogRecord>(line) {
+            Ok(record) => record,
+            Err(_) => {
+                skipped += 1;
+                continue;
+            }
+        };
+
+        match record {
+            LogRecord::Sample(sample) => {
+                data.cpu.extend(sample.cpu);
+                data.memory.extend(sample.memory);
+                data.network.extend(sample.network);
+                data.disk.extend(sample.disk);
+                data.pressure.extend(sample.pressure);
+                data.cgroup.extend(sample.cgroup);
+                data.processes.extend(sample.processes);
+            }
+            LogRecord::Trace(TraceEvent::Started(process)) => {
+                traced.insert((process.pid, process.start_time), data.process_trace.len());
+                data.process_trace.push(process);
+            }
+            LogRecord::Trace(TraceEvent::Exited { pid, start_time, end_time }) => {
+                if let Some(&index) = traced.get(&(pid, start_time)) {
+                    data.process_trace[index].end_time = Some(end_time);
+                }
+            }
+            LogRecord::Command(command) => data.command = Some(command),
+            LogRecord::Mark(mark) => {
+                // 次のステップの開始は実行中のステップの終了を兼ねる
+                if let Some(step) = data.steps.last_mut().filter(|step| step.end_time.is_none()) {
+                    step.end_time = Some(mark.time);
+                }
+                if let Some(name) = mark.name {
+                    data.steps.push(Step { name, start_time: mark.time, end_time: None });
+                }
+            }
+        }
+    }
+
+    if skipped > 0 {
+        eprintln!("⚠️ Skipped {} unreadable line(s) in sample log", skipped);
+    }
+
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collectors::TracedProcess;
+
+    fn cpu(time: u64, total_load: f64) -> CpuStats {
+        CpuStats {
+            time,
+            total_load,
+            user_load: total_load,
+            system_load: 0.0,
+            iowait_load: 0.0,
+            irq_load: 0.0,
+            softirq_load: 0.0,
+            steal_load: 0.0,
+            per_core: vec![],
+        }
+    }
+
+    fn to_line(record: &LogRecord) -> String {
+        serde_json::to_string(record).unwrap()
+    }
+
+    #[test]
+    fn test_parse_truncated_sample_log() {
+        let started = TracedProcess {
+            pid: 42,
+            ppid: 1,
+            comm: "rustc".to_string(),
+            cmdline: "rustc main.rs".to_string(),
+            start_time: 1500,
+            end_time: None,
+        };
+        let lines = [
+            to_line(&LogRecord::Sample(Box::new(Sample { cpu: Some(cpu(1000, 10.0)), ..Default::default() }))),
+            to_line(&LogRecord::Trace(TraceEvent::Started(started))),
+            to_line(&LogRecord::Sample(Box::new(Sample { cpu: Some(cpu(2000, 20.0)), ..Default::default() }))),
+            to_line(&LogRecord::Trace(TraceEvent::Exited { pid: 42, start_time: 1500, end_time: 2500 })),
+        ];
+        // 最後の行が書き込み途中で切れている
+        let content = format!("{}\n{}", lines.join("\n"), r#"{"sample":{"cpu":{"time":30"#);
+
+        let data = parse_sample_log(&content);
+
+        assert_eq!(data.cpu.len(), 2);
+        assert_eq!(data.cpu[1].total_load, 20.0);
+        assert_eq!(data.process_trace.len(), 1);
+        assert_eq!(data.process_trace[0].end_time, Some(2500));
+    }
+
+    #[test]
+    fn test_read_telemetry_legacy_json() {
+        let path = std::env::temp_dir().join(format!("telemetry-legacy-{}.json", std::process::id()));
+        let data = TelemetryData { cpu: vec![cpu(1000, 10.0)], ..Default::default() };
+        fs::write(&path, serde_json::to_string(&data).unwrap()).unwrap();
+
+        let read = read_telemetry(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(read.cpu.len(), 1);
+    }
+
+    #[test]
+    fn test_read_telemetry_truncated_mid_character() {
+        let path = std::env::temp_dir().join(format!("telemetry-truncated-{}.ndjson", std::process::id()));
+        let sample = to_line(&LogRecord::Sample(Box::new(Sample { cpu: Some(cpu(1000, 10.0)), ..Default::default() })));
+        let command = to_line(&LogRecord::Command(CommandRun { command: "echo ビルド".to_string(), ..Default::default() }));
+        // 最後の行が "ビ" (3バイト) の途中で切れている
+        let cut = command.find('ビ').unwrap() + 1;
+        let mut content = format!("{}\n", sample).into_bytes();
+        content.extend_from_slice(&command.as_bytes()[..cut]);
+        fs::write(&path, content).unwrap();
+
+        let read = read_telemetry(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(read.cpu.len(), 1);
+        assert!(read.command.is_none());
+    }
+}
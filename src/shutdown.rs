@@ -0,0 +1,299 @@
+use anyhow::{bail, Context, Result};
+use std::os::unix::process::CommandExt;
+use std::process::Command;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 記録の停止を要求したシグナル
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// SIGTERM: post.js からの通常の停止要求
+    Term,
+    /// SIGINT: 手元での Ctrl+C による中断
+    Int,
+    /// SIGHUP: 制御端末やセッションの切断
+    Hup,
+}
+
+impl Signal {
+    fn from_raw(signo: libc::c_int) -> Option<Self> {
+        match signo {
+            libc::SIGTERM => Some(Self::Term),
+            libc::SIGINT => Some(Self::Int),
+            libc::SIGHUP => Some(Self::Hup),
+            _ => None,
+        }
+    }
+
+    fn raw(self) -> libc::c_int {
+        match self {
+            Self::Term => libc::SIGTERM,
+            Self::Int => libc::SIGINT,
+            Self::Hup => libc::SIGHUP,
+        }
+    }
+
+    /// シグナル名
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Term => "SIGTERM",
+            Self::Int => "SIGINT",
+            Self::Hup => "SIGHUP",
+        }
+    }
+
+    /// データを保存し終えた後の終了ステータス
+    ///
+    /// SIGTERM は想定どおりの停止なので 0、それ以外は 128 + シグナル番号 (シェルの慣例)
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::Term => 0,
+            Self::Int | Self::Hup => 128 + self.raw(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    stopped: bool,
+    signal: Option<Signal>,
+}
+
+/// サンプリングループとトレーサースレッドに停止を伝える
+///
+/// シグナルハンドラーは使わず、対象シグナルをブロックした上で専用スレッドの
+/// `sigwait` で受け取る。ロックやI/Oは通常のスレッド上でしか行わないので、
+/// シグナル受信時にデッドロックすることはない。
+#[derive(Debug, Default)]
+pub struct Shutdown {
+    state: Mutex<State>,
+    condvar: Condvar,
+    /// シグナルを転送する子プロセスのPID (0なら転送しない)
+    ///
+    /// 転送はロックを持ったまま kill するので、`forward_to(0)` から戻った後に古いPIDへは送らない
+    forward_pid: Mutex<libc::pid_t>,
+}
+
+/// 停止要求として受け取るシグナルの集合
+fn shutdown_signals() -> libc::sigset_t {
+    // SAFETY: sigset_t はゼロ初期化後に sigemptyset で初期化してから使う
+    let mut set: libc::sigset_t = unsafe { std::mem::zeroed() };
+    unsafe {
+        libc::sigemptyset(&mut set);
+        libc::sigaddset(&mut set, libc::SIGTERM);
+        libc::sigaddset(&mut set, libc::SIGINT);
+        libc::sigaddset(&mut set, libc::SIGHUP);
+    }
+    set
+}
+
+/// `Shutdown::install` でブロックしたシグナルを、起動する子プロセスでは元に戻す
+///
+/// シグナルマスクは exec 後も引き継がれるため、戻さないと子プロセスが SIGTERM などで終了しなくなる。
+pub fn unblock_in_child(command: &mut Command) -> &mut Command {
+    let set = shutdown_signals();
+    // SAFETY: fork 後の子プロセスでは async-signal-safe な pthread_sigmask だけを呼ぶ
+    unsafe {
+        command.pre_exec(move || {
+            libc::pthread_sigmask(libc::SIG_UNBLOCK, &set, std::ptr::null_mut());
+            Ok(())
+        })
+    }
+}
+
+impl Shutdown {
+    /// SIGTERM / SIGINT / SIGHUP の受信を開始
+    ///
+    /// シグナルマスクは以降に作成したスレッドに引き継がれるので、他のスレッドを作る前に呼ぶこと。
+    /// 子プロセスを起動するときは `unblock_in_child` でマスクを戻すこと。
+    pub fn install() -> Result<Arc<Self>> {
+        let set = shutdown_signals();
+
+        // SAFETY: 有効な sigset_t を渡し、古いマスクは受け取らない
+        let ret = unsafe { libc::pthread_sigmask(libc::SIG_BLOCK, &set, std::ptr::null_mut()) };
+        if ret != 0 {
+            bail!("Failed to block signals: {}", std::io::Error::from_raw_os_error(ret));
+        }
+
+        let shutdown = Arc::new(Self::default());
+        let receiver = shutdown.clone();
+        thread::Builder::new()
+            .name("signal".to_string())
+            .spawn(move || loop {
+                let mut signo: libc::c_int = 0;
+                // SAFETY: set はブロック済みのシグナル集合、signo は有効なポインタ
+                if unsafe { libc::sigwait(&set, &mut signo) } != 0 {
+                    continue;
+                }
+                let Some(signal) = Signal::from_raw(signo) else {
+                    continue;
+                };
+
+                // 子プロセスを実行中なら転送し、停止は子プロセスの終了を待つ
+                let forwarded = {
+                    let pid = *receiver.forward_pid.lock().unwrap();
+                    if pid > 0 {
+                        eprintln!("Received {}, forwarding to pid {}", signal.name(), pid);
+                        // SAFETY: kill はシグナルを送るだけ。転送中の子プロセスはまだ回収されていない
+                        // (`wait_child`) ので、PIDが別のプロセスに再利用されていることはない
+                        unsafe { libc::kill(pid, signo) };
+                    }
+                    pid > 0
+                };
+                if forwarded {
+                    receiver.state.lock().unwrap().signal.get_or_insert(signal);
+                    continue;
+                }
+
+                // 保存中に再度シグナルを受けたら待たずに終了する
+                if receiver.signal().is_some() {
+                    eprintln!("Received {} again, exiting without saving", signal.name());
+                    std::process::exit(signal.exit_code());
+                }
+                eprintln!("Received {}, taking a final sample...", signal.name());
+                receiver.notify(Some(signal));
+            })
+            .context("Failed to spawn signal thread")?;
+
+        Ok(shutdown)
+    }
+
+    /// 受信したシグナルを `pid` の子プロセスに転送する (停止は `stop` で行う)
+    pub fn forward_to(&self, pid: u32) {
+        *self.forward_pid.lock().unwrap() = pid as libc::pid_t;
+    }
+
+    /// 子プロセスの終了を待ち、転送をやめてから `reap` で回収する
+    ///
+    /// 終了は `waitid(WNOWAIT)` で待つので、転送をやめるまではPIDがゾンビとして残り、
+    /// 回収後に同じPIDを再利用した別のプロセスへシグナルを送ることはない。
+    pub fn wait_child<T>(&self, pid: u32, reap: impl FnOnce() -> Result<T>) -> Result<T> {
+        loop {
+            // SAFETY: siginfo_t はゼロ初期化した構造体で、waitid が書き込む
+            let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+            // SAFETY: 自分で起動した子プロセスを待つだけで、WNOWAIT なので回収はしない
+            let ret = unsafe { libc::waitid(libc::P_PID, pid, &mut info, libc::WEXITED | libc::WNOWAIT) };
+            if ret == 0 {
+                break;
+            }
+            let err = std::io::Error::last_os_error();
+            if err.kind() != std::io::ErrorKind::Interrupted {
+                bail!("Failed to wait for pid {}: {}", pid, err);
+            }
+        }
+        self.forward_to(0);
+        reap()
+    }
+
+    /// シグナルによらない停止 (最大回数に達したときなど)
+    pub fn stop(&self) {
+        self.notify(None);
+    }
+
+    /// 停止が要求されているか
+    pub fn is_stopped(&self) -> bool {
+        self.state.lock().unwrap().stopped
+    }
+
+    /// 停止を要求したシグナル
+    pub fn signal(&self) -> Option<Signal> {
+        self.state.lock().unwrap().signal
+    }
+
+    /// 最大 `timeout` 待つ。途中で停止が要求されたらすぐに戻り、trueを返す
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.state.lock().unwrap();
+        while !state.stopped {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            state = self.condvar.wait_timeout(state, deadline - now).unwrap().0;
+        }
+        state.stopped
+    }
+
+    fn notify(&self, signal: Option<Signal>) {
+        let mut state = self.state.lock().unwrap();
+        state.stopped = true;
+        if state.signal.is_none() {
+            state.signal = signal;
+        }
+        self.condvar.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signal_exit_code() {
+        assert_eq!(Signal::from_raw(libc::SIGTERM), Some(Signal::Term));
+        assert_eq!(Signal::from_raw(libc::SIGUSR1), None);
+        assert_eq!(Signal::Term.exit_code(), 0);
+        assert_eq!(Signal::Int.exit_code(), 130);
+        assert_eq!(Signal::Hup.exit_code(), 129);
+    }
+
+    #[test]
+    fn test_wait_timeout_wakes_on_signal() {
+        let shutdown = Arc::new(Shutdown::default());
+        assert!(!shutdown.wait_timeout(Duration::from_millis(10)));
+
+        let notifier = shutdown.clone();
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            notifier.notify(Some(Signal::Hup));
+        });
+
+        let started = Instant::now();
+        assert!(shutdown.wait_timeout(Duration::from_secs(30)));
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(shutdown.signal(), Some(Signal::Hup));
+        handle.join().unwrap();
+
+        // 後から stop しても最初のシグナルは上書きしない
+        shutdown.stop();
+        assert_eq!(shutdown.signal(), Some(Signal::Hup));
+    }
+
+    #[test]
+    fn test_wait_child_stops_forwarding_before_reaping() {
+        let shutdown = Shutdown::default();
+        let mut child = Command::new("sh").args(["-c", "exit 3"]).spawn().expect("Failed to spawn");
+        shutdown.forward_to(child.id());
+
+        let status = shutdown
+            .wait_child(child.id(), || {
+                // 回収する時点で転送は止まっている
+                assert_eq!(*shutdown.forward_pid.lock().unwrap(), 0);
+                Ok(child.wait()?)
+            })
+            .unwrap();
+        assert_eq!(status.code(), Some(3));
+    }
+
+    #[test]
+    fn test_unblock_in_child() {
+        let set = shutdown_signals();
+        let mut command = Command::new("grep");
+        command.args(["SigBlk", "/proc/self/status"]);
+        unblock_in_child(&mut command);
+
+        // ブロックしたスレッドから起動しても子プロセスではブロックされていない
+        let output = thread::spawn(move || {
+            unsafe { libc::pthread_sigmask(libc::SIG_BLOCK, &set, std::ptr::null_mut()) };
+            command.output().expect("Failed to run grep")
+        })
+        .join()
+        .unwrap();
+
+        let mask = String::from_utf8_lossy(&output.stdout);
+        let mask = u64::from_str_radix(mask.trim().trim_start_matches("SigBlk:").trim(), 16).unwrap();
+        assert_eq!(mask & (1 << (libc::SIGTERM - 1)), 0);
+        assert_eq!(mask & (1 << (libc::SIGINT - 1)), 0);
+    }
+}